@@ -0,0 +1,132 @@
+use criterion::{black_box, criterion_group, BatchSize, Criterion};
+use host_port_pair::{CompactHostPortPair, HostPortPair};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    mem,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+const SHORT: &str = "api.example.com:443";
+const LONG: &str = "service-7.internal.eu-west-1.example.com:443";
+
+// Returns the bytes and number of allocations used by 100k values, including the `Vec` itself.
+fn heap_usage<T>(make: impl Fn(usize) -> T) -> (usize, usize) {
+    let bytes = ALLOCATED.load(Ordering::Relaxed);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let items = (0..100_000).map(make).collect::<Vec<_>>();
+
+    let usage = (
+        ALLOCATED.load(Ordering::Relaxed) - bytes,
+        ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+    );
+
+    drop(items);
+    usage
+}
+
+// Both types are 40 bytes, so there is no size change. The difference is in heap usage: a
+// `HostPortPair` allocates a copy of its name per clone, and a `CompactHostPortPair` doesn't.
+fn report_memory() {
+    println!(
+        "size_of: HostPortPair = {} bytes, CompactHostPortPair = {} bytes",
+        mem::size_of::<HostPortPair>(),
+        mem::size_of::<CompactHostPortPair>(),
+    );
+
+    for (label, s) in [("short", SHORT), ("long", LONG)] {
+        let pair = HostPortPair::try_from(s).unwrap();
+        let compact = CompactHostPortPair::try_from(s).unwrap();
+
+        let (pair_bytes, pair_allocations) = heap_usage(|_| pair.clone());
+        let (compact_bytes, compact_allocations) = heap_usage(|_| compact.clone());
+
+        println!(
+            "100k clones of {label} name: \
+             HostPortPair = {pair_bytes} bytes in {pair_allocations} allocations, \
+             CompactHostPortPair = {compact_bytes} bytes in {compact_allocations} allocations",
+        );
+    }
+}
+
+fn bench_clone(c: &mut Criterion) {
+    let mut group = c.benchmark_group("clone");
+
+    for (label, s) in [("short", SHORT), ("long", LONG)] {
+        let pair = HostPortPair::try_from(s).unwrap();
+        let compact = CompactHostPortPair::try_from(s).unwrap();
+
+        group.bench_function(format!("{label}/host_port_pair"), |b| {
+            b.iter(|| black_box(&pair).clone())
+        });
+
+        group.bench_function(format!("{label}/compact"), |b| {
+            b.iter(|| black_box(&compact).clone())
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_drop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("drop");
+
+    for (label, s) in [("short", SHORT), ("long", LONG)] {
+        let pair = HostPortPair::try_from(s).unwrap();
+        let compact = CompactHostPortPair::try_from(s).unwrap();
+
+        group.bench_function(format!("{label}/host_port_pair"), |b| {
+            b.iter_batched(|| pair.clone(), drop, BatchSize::SmallInput)
+        });
+
+        group.bench_function(format!("{label}/compact"), |b| {
+            b.iter_batched(|| compact.clone(), drop, BatchSize::SmallInput)
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+
+    for (label, s) in [("short", SHORT), ("long", LONG)] {
+        group.bench_function(format!("{label}/host_port_pair"), |b| {
+            b.iter(|| HostPortPair::try_from(black_box(s)).unwrap())
+        });
+
+        group.bench_function(format!("{label}/compact"), |b| {
+            b.iter(|| CompactHostPortPair::try_from(black_box(s)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_clone, bench_drop, bench_parse);
+
+fn main() {
+    report_memory();
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,342 @@
+use crate::{
+    dns_name_eq, hash_dns_name, interface_index,
+    parse::{parse_ip_host, parse_strict_host, split_host_port},
+    to_ascii_name, DnsName, DnsNameError, Host, HostPortPair, ParseError, ParseErrorKind, ZoneId,
+};
+use alloc::{borrow::ToOwned, boxed::Box};
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    hash::{Hash, Hasher},
+    net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6},
+};
+
+/// A borrowed [`HostPortPair`] that parses without allocating.
+///
+/// Parsing rejects internationalized domain names, which [`HostPortPair`] converts to A-labels, so
+/// that a borrowed pair always equals and hashes like its owned form.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HostPortPairRef<'a> {
+    pub(crate) host: HostRef<'a>,
+    pub(crate) port: u16,
+}
+
+/// A borrowed [`Host`].
+#[derive(Debug, Clone, Copy)]
+pub enum HostRef<'a> {
+    IpAddr(IpAddr),
+    ScopedIpv6(Ipv6Addr, ZoneIdRef<'a>),
+    DnsName(&'a str),
+}
+
+/// A borrowed [`ZoneId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ZoneIdRef<'a> {
+    Index(u32),
+    Name(&'a str),
+}
+
+impl<'a> HostPortPairRef<'a> {
+    pub fn host(&self) -> HostRef<'a> {
+        self.host
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn as_socket_addr(&self) -> Option<SocketAddr> {
+        match self.host {
+            HostRef::IpAddr(ip) => Some(SocketAddr::new(ip, self.port)),
+            HostRef::ScopedIpv6(ip, zone) => Some(SocketAddr::V6(SocketAddrV6::new(
+                ip,
+                self.port,
+                0,
+                zone.index()?,
+            ))),
+            HostRef::DnsName(_) => None,
+        }
+    }
+
+    pub fn to_owned(&self) -> HostPortPair {
+        HostPortPair {
+            host: self.host.to_owned(),
+            port: self.port,
+        }
+    }
+}
+
+impl<'a> HostRef<'a> {
+    /// Parses an IP address or an RFC 1123 hostname without allocating.
+    ///
+    /// This applies the same rules as [`Host::parse`], except that internationalized names are
+    /// always rejected since converting them to A-labels requires an allocation.
+    pub fn parse(s: &'a str) -> Result<Self, ParseError> {
+        parse_strict_host(s)
+    }
+
+    pub fn is_ip_address(&self) -> bool {
+        matches!(self, HostRef::IpAddr(_) | HostRef::ScopedIpv6(..))
+    }
+
+    pub fn is_dns_name(&self) -> bool {
+        matches!(self, HostRef::DnsName(_))
+    }
+
+    pub fn ip_addr(&self) -> Option<IpAddr> {
+        match *self {
+            HostRef::IpAddr(ip) => Some(ip),
+            HostRef::ScopedIpv6(ip, _) => Some(IpAddr::V6(ip)),
+            HostRef::DnsName(_) => None,
+        }
+    }
+
+    pub fn zone_id(&self) -> Option<ZoneIdRef<'a>> {
+        match *self {
+            HostRef::ScopedIpv6(_, zone) => Some(zone),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn fmt_bracketed(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HostRef::IpAddr(IpAddr::V6(ip)) => write!(f, "[{ip}]"),
+            // The RFC 6874 form, so that zones starting with `25` parse back unchanged.
+            HostRef::ScopedIpv6(ip, zone) => write!(f, "[{ip}%25{zone}]"),
+            host => write!(f, "{host}"),
+        }
+    }
+
+    pub fn to_owned(&self) -> Host {
+        match *self {
+            HostRef::IpAddr(ip) => Host::IpAddr(ip),
+            HostRef::ScopedIpv6(ip, zone) => Host::ScopedIpv6(ip, zone.to_owned()),
+            HostRef::DnsName(name) => Host::DnsName(to_ascii_name(name.to_owned())),
+        }
+    }
+}
+
+impl ZoneIdRef<'_> {
+    pub fn index(&self) -> Option<u32> {
+        match *self {
+            ZoneIdRef::Index(index) => Some(index),
+            ZoneIdRef::Name(name) => interface_index(name),
+        }
+    }
+
+    pub fn to_owned(&self) -> ZoneId {
+        match *self {
+            ZoneIdRef::Index(index) => ZoneId::Index(index),
+            ZoneIdRef::Name(name) => ZoneId::Name(Box::new(DnsName::from(name))),
+        }
+    }
+}
+
+impl PartialEq for HostRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (HostRef::IpAddr(a), HostRef::IpAddr(b)) => a == b,
+            (HostRef::ScopedIpv6(a, a_zone), HostRef::ScopedIpv6(b, b_zone)) => {
+                a == b && a_zone == b_zone
+            }
+            (HostRef::DnsName(a), HostRef::DnsName(b)) => dns_name_eq(a, b),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for HostRef<'_> {}
+
+impl Hash for HostRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            HostRef::IpAddr(IpAddr::V4(ip)) => {
+                state.write_u8(0);
+                state.write(&ip.octets());
+            }
+            HostRef::IpAddr(IpAddr::V6(ip)) => {
+                state.write_u8(1);
+                state.write(&ip.octets());
+            }
+            HostRef::ScopedIpv6(ip, zone) => {
+                state.write_u8(2);
+                state.write(&ip.octets());
+                zone.hash(state);
+            }
+            HostRef::DnsName(name) => {
+                state.write_u8(3);
+                hash_dns_name(name, state);
+            }
+        }
+    }
+}
+
+impl Hash for ZoneIdRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self {
+            ZoneIdRef::Index(index) => {
+                state.write_u8(0);
+                state.write_u32(*index);
+            }
+            ZoneIdRef::Name(name) => {
+                state.write_u8(1);
+                state.write(name.as_bytes());
+                state.write_u8(0xff);
+            }
+        }
+    }
+}
+
+impl From<IpAddr> for HostRef<'_> {
+    fn from(ip: IpAddr) -> Self {
+        HostRef::IpAddr(ip)
+    }
+}
+
+/// Treats anything that isn't an IP address as a DNS name, like the `From` conversions of [`Host`].
+///
+/// Unlike them, this keeps internationalized names as they are instead of converting them to
+/// A-labels, so with the `idna` feature the result may not equal the owned form of the same
+/// string. [`HostRef::parse`] and the `TryFrom` conversion of [`HostPortPairRef`] reject such
+/// names instead.
+impl<'a> From<&'a str> for HostRef<'a> {
+    fn from(host: &'a str) -> Self {
+        parse_ip_host(host).unwrap_or(HostRef::DnsName(host))
+    }
+}
+
+impl<'a> From<&'a Host> for HostRef<'a> {
+    fn from(host: &'a Host) -> Self {
+        host.as_borrowed()
+    }
+}
+
+impl<'a> From<&'a ZoneId> for ZoneIdRef<'a> {
+    fn from(zone: &'a ZoneId) -> Self {
+        zone.as_borrowed()
+    }
+}
+
+impl<'a, T: Into<HostRef<'a>>> From<(T, u16)> for HostPortPairRef<'a> {
+    fn from((host, port): (T, u16)) -> Self {
+        HostPortPairRef {
+            host: host.into(),
+            port,
+        }
+    }
+}
+
+impl<'a> From<&'a HostPortPair> for HostPortPairRef<'a> {
+    fn from(pair: &'a HostPortPair) -> Self {
+        pair.as_borrowed()
+    }
+}
+
+impl<'a> TryFrom<&'a str> for HostPortPairRef<'a> {
+    type Error = ParseError;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        let (host, port) = split_host_port(s)?;
+
+        Ok(HostPortPairRef {
+            host: reject_non_ascii_name(host)?,
+            port,
+        })
+    }
+}
+
+// Owned hosts store internationalized names as A-labels, which a borrowed host can't hold without
+// allocating, so they are rejected like in `HostRef::parse`. Otherwise equal borrowed and owned
+// hosts would compare and hash differently. Names are never bracketed, so spans start at the
+// beginning of the name.
+pub(crate) fn reject_non_ascii_name(host: HostRef<'_>) -> Result<HostRef<'_>, ParseError> {
+    if let HostRef::DnsName(name) = host {
+        if let Some((i, c)) = name.char_indices().find(|(_, c)| !c.is_ascii()) {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidDnsName(DnsNameError::InvalidChar(c)),
+                i..i + c.len_utf8(),
+            ));
+        }
+    }
+
+    Ok(host)
+}
+
+impl Display for HostRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HostRef::IpAddr(ip) => write!(f, "{ip}"),
+            HostRef::ScopedIpv6(ip, zone) => write!(f, "{ip}%{zone}"),
+            HostRef::DnsName(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl Display for ZoneIdRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ZoneIdRef::Index(index) => write!(f, "{index}"),
+            ZoneIdRef::Name(name) => write!(f, "{name}"),
+        }
+    }
+}
+
+impl Display for HostPortPairRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.host.fmt_bracketed(f)?;
+        write!(f, ":{}", self.port)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn internationalized_names() {
+        let err = HostPortPairRef::try_from("bücher.example:443").unwrap_err();
+        assert_eq!(
+            err.kind(),
+            &ParseErrorKind::InvalidDnsName(DnsNameError::InvalidChar('ü'))
+        );
+        assert_eq!(err.span(), 1..3);
+        assert!(HostPortPair::try_from("bücher.example:443").is_ok());
+
+        let owned = HostPortPair::try_from("Example.COM.:443").unwrap();
+        assert_eq!(
+            HostPortPairRef::try_from("Example.COM.:443").unwrap(),
+            owned.as_borrowed()
+        );
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn lenient_names_differ_from_owned() {
+        assert_ne!(
+            HostRef::from("bücher.example"),
+            Host::from("bücher.example").as_borrowed()
+        );
+        assert_eq!(
+            HostRef::from("Example.COM."),
+            Host::from("Example.COM.").as_borrowed()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn deserialize_rejects_internationalized_names() {
+        use serde::{de::value::BorrowedStrDeserializer, Deserialize};
+
+        let de = |s| BorrowedStrDeserializer::<serde::de::value::Error>::new(s);
+
+        assert!(HostRef::deserialize(de("bücher.example")).is_err());
+        assert!(HostPortPairRef::deserialize(de("bücher.example:80")).is_err());
+        assert_eq!(
+            HostRef::deserialize(de("example.com")).unwrap(),
+            HostRef::DnsName("example.com")
+        );
+        assert_eq!(
+            HostRef::deserialize(de("::1")).unwrap(),
+            HostRef::IpAddr(IpAddr::V6(Ipv6Addr::LOCALHOST))
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{is_in_domain, Host, HostRef};
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+/// A special-use domain name reserved by RFC 6761 and related documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpecialUseDomain {
+    /// `localhost` and names under it, which resolve to loopback addresses (RFC 6761).
+    Localhost,
+    /// Names under `local`, which are resolved with multicast DNS (RFC 6762).
+    Local,
+    /// Names under `onion`, which are Tor onion services (RFC 7686).
+    Onion,
+    /// `invalid` and names under it, which never resolve (RFC 6761).
+    Invalid,
+    /// `test` and names under it, for testing (RFC 6761).
+    Test,
+    /// `example`, `example.com`, `example.net`, `example.org` and names under them, for
+    /// documentation (RFC 6761).
+    Example,
+    /// Names under `internal`, reserved for private use by ICANN.
+    Internal,
+}
+
+const SPECIAL_USE_DOMAINS: &[(&str, SpecialUseDomain)] = &[
+    ("localhost", SpecialUseDomain::Localhost),
+    ("local", SpecialUseDomain::Local),
+    ("onion", SpecialUseDomain::Onion),
+    ("invalid", SpecialUseDomain::Invalid),
+    ("test", SpecialUseDomain::Test),
+    ("example", SpecialUseDomain::Example),
+    ("example.com", SpecialUseDomain::Example),
+    ("example.net", SpecialUseDomain::Example),
+    ("example.org", SpecialUseDomain::Example),
+    ("internal", SpecialUseDomain::Internal),
+];
+
+// IPv4-mapped IPv6 addresses are classified as the IPv4 addresses they carry.
+impl HostRef<'_> {
+    /// Returns whether the host is a loopback address, or `localhost` or a name under it.
+    pub fn is_loopback(&self) -> bool {
+        match self.classified_ip() {
+            Some(IpAddr::V4(ip)) => ip.is_loopback(),
+            Some(IpAddr::V6(ip)) => ip.is_loopback(),
+            None => self.special_use_domain() == Some(SpecialUseDomain::Localhost),
+        }
+    }
+
+    /// Returns whether the host is an RFC 1918 IPv4 address or an IPv6 unique local address.
+    pub fn is_private(&self) -> bool {
+        match self.classified_ip() {
+            Some(IpAddr::V4(ip)) => ip.is_private(),
+            Some(IpAddr::V6(ip)) => ip.is_unique_local(),
+            None => false,
+        }
+    }
+
+    pub fn is_link_local(&self) -> bool {
+        match self.classified_ip() {
+            Some(IpAddr::V4(ip)) => ip.is_link_local(),
+            Some(IpAddr::V6(ip)) => ip.is_unicast_link_local(),
+            None => false,
+        }
+    }
+
+    pub fn is_unspecified(&self) -> bool {
+        match self.classified_ip() {
+            Some(IpAddr::V4(ip)) => ip.is_unspecified(),
+            Some(IpAddr::V6(ip)) => ip.is_unspecified(),
+            None => false,
+        }
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        match self.classified_ip() {
+            Some(IpAddr::V4(ip)) => ip.is_multicast(),
+            Some(IpAddr::V6(ip)) => ip.is_multicast(),
+            None => false,
+        }
+    }
+
+    /// Returns whether the host is an address that is reachable on the public internet, following
+    /// the IANA special-purpose address registries. DNS names are never global.
+    pub fn is_global(&self) -> bool {
+        match self.classified_ip() {
+            Some(IpAddr::V4(ip)) => is_global_ipv4(ip),
+            Some(IpAddr::V6(ip)) => is_global_ipv6(ip),
+            None => false,
+        }
+    }
+
+    pub fn special_use_domain(&self) -> Option<SpecialUseDomain> {
+        let HostRef::DnsName(name) = *self else {
+            return None;
+        };
+
+        SPECIAL_USE_DOMAINS
+            .iter()
+            .find(|(domain, _)| is_in_domain(name, domain))
+            .map(|(_, kind)| *kind)
+    }
+
+    pub fn is_special_use_name(&self) -> bool {
+        self.special_use_domain().is_some()
+    }
+
+    fn classified_ip(&self) -> Option<IpAddr> {
+        match self.ip_addr()? {
+            IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+                Some(ip) => Some(IpAddr::V4(ip)),
+                None => Some(IpAddr::V6(ip)),
+            },
+            ip => Some(ip),
+        }
+    }
+}
+
+impl Host {
+    /// Returns whether the host is a loopback address, or `localhost` or a name under it.
+    pub fn is_loopback(&self) -> bool {
+        self.as_borrowed().is_loopback()
+    }
+
+    /// Returns whether the host is an RFC 1918 IPv4 address or an IPv6 unique local address.
+    pub fn is_private(&self) -> bool {
+        self.as_borrowed().is_private()
+    }
+
+    pub fn is_link_local(&self) -> bool {
+        self.as_borrowed().is_link_local()
+    }
+
+    pub fn is_unspecified(&self) -> bool {
+        self.as_borrowed().is_unspecified()
+    }
+
+    pub fn is_multicast(&self) -> bool {
+        self.as_borrowed().is_multicast()
+    }
+
+    /// Returns whether the host is an address that is reachable on the public internet, following
+    /// the IANA special-purpose address registries. DNS names are never global.
+    pub fn is_global(&self) -> bool {
+        self.as_borrowed().is_global()
+    }
+
+    pub fn special_use_domain(&self) -> Option<SpecialUseDomain> {
+        self.as_borrowed().special_use_domain()
+    }
+
+    pub fn is_special_use_name(&self) -> bool {
+        self.as_borrowed().is_special_use_name()
+    }
+}
+
+fn is_global_ipv4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, d] = ip.octets();
+
+    !(a == 0
+        || ip.is_private()
+        || ip.is_loopback()
+        || ip.is_link_local()
+        // Shared address space (RFC 6598).
+        || (a == 100 && b & 0xc0 == 64)
+        // IETF protocol assignments, except the globally reachable anycast addresses.
+        || (a == 192 && b == 0 && c == 0 && d != 9 && d != 10)
+        || ip.is_documentation()
+        // Benchmarking (RFC 2544).
+        || (a == 198 && b & 0xfe == 18)
+        // Reserved, including the broadcast address.
+        || a >= 240
+        // Link-local multicast (RFC 5771).
+        || (a == 224 && b == 0 && c == 0))
+}
+
+fn is_global_ipv6(ip: Ipv6Addr) -> bool {
+    let segments = ip.segments();
+
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        // IPv4-IPv6 translation (RFC 8215).
+        || (segments[0] == 0x64 && segments[1] == 0xff9b && segments[2] == 1)
+        // Discard-only (RFC 6666).
+        || (segments[0] == 0x100 && segments[1..4] == [0, 0, 0])
+        // IETF protocol assignments, except the globally reachable ones.
+        || (segments[0] == 0x2001 && segments[1] < 0x200 && !is_global_ietf_ipv6(segments))
+        // Documentation (RFC 3849 and RFC 9637).
+        || (segments[0] == 0x2001 && segments[1] == 0xdb8)
+        || (segments[0] == 0x3fff && segments[1] < 0x1000)
+        // 6to4 (RFC 3056).
+        || segments[0] == 0x2002
+        // Segment routing (RFC 9602).
+        || segments[0] == 0x5f00
+        || ip.is_unique_local()
+        || ip.is_unicast_link_local()
+        // Multicast with a scope smaller than global.
+        || (ip.is_multicast() && segments[0] & 0x000f != 0xe))
+}
+
+// The globally reachable parts of 2001::/23: PCP and TURN anycast, AMT, AS112-v6 and ORCHIDv2.
+fn is_global_ietf_ipv6(segments: [u16; 8]) -> bool {
+    let ip = u128::from(Ipv6Addr::from(segments));
+
+    ip == 0x2001_0001_0000_0000_0000_0000_0000_0001
+        || ip == 0x2001_0001_0000_0000_0000_0000_0000_0002
+        || segments[1] == 3
+        || (segments[1] == 4 && segments[2] == 0x112)
+        || (segments[1] & 0xfff0 == 0x20)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> Host {
+        Host::IpAddr(s.parse().unwrap())
+    }
+
+    #[test]
+    fn global_ipv4() {
+        let cases = [
+            ("8.8.8.8", true),
+            ("1.1.1.1", true),
+            ("0.1.2.3", false),
+            ("10.0.0.1", false),
+            ("127.0.0.1", false),
+            ("169.254.1.1", false),
+            ("172.16.0.1", false),
+            ("192.168.1.1", false),
+            ("100.63.255.255", true),
+            ("100.64.0.1", false),
+            ("100.127.255.255", false),
+            ("100.128.0.0", true),
+            ("192.0.0.8", false),
+            ("192.0.0.9", true),
+            ("192.0.0.10", true),
+            ("192.0.0.11", false),
+            ("192.0.1.1", true),
+            ("192.0.2.1", false),
+            ("198.18.0.1", false),
+            ("198.19.255.255", false),
+            ("198.20.0.1", true),
+            ("224.0.0.1", false),
+            ("224.0.1.1", true),
+            ("240.0.0.1", false),
+            ("255.255.255.255", false),
+        ];
+
+        for (s, global) in cases {
+            assert_eq!(ip(s).is_global(), global, "{s}");
+        }
+    }
+
+    #[test]
+    fn global_ipv6() {
+        let cases = [
+            ("2606:4700::1111", true),
+            ("::", false),
+            ("::1", false),
+            ("64:ff9b:1::1", false),
+            ("64:ff9b::808:808", true),
+            ("100::1", false),
+            ("2001::1", false),
+            ("2001:1::1", true),
+            ("2001:1::2", true),
+            ("2001:1::4", false),
+            ("2001:2::1", false),
+            ("2001:3::1", true),
+            ("2001:4:112::1", true),
+            ("2001:4:113::1", false),
+            ("2001:10::1", false),
+            ("2001:20::1", true),
+            ("2001:2f::1", true),
+            ("2001:30::1", false),
+            ("2001:200::1", true),
+            ("2001:db8::1", false),
+            ("3fff::1", false),
+            ("3fff:1000::1", true),
+            ("2002::1", false),
+            ("5f00::1", false),
+            ("fc00::1", false),
+            ("fe80::1", false),
+            ("ff01::1", false),
+            ("ff02::1", false),
+            ("ff05::1", false),
+            ("ff08::1", false),
+            ("ff0e::1", true),
+            ("ff1e::1", true),
+        ];
+
+        for (s, global) in cases {
+            assert_eq!(ip(s).is_global(), global, "{s}");
+        }
+    }
+
+    #[test]
+    fn ipv4_mapped() {
+        let cases = [
+            ("::ffff:8.8.8.8", true, false, false),
+            ("::ffff:127.0.0.1", false, true, false),
+            ("::ffff:10.0.0.1", false, false, true),
+            ("::ffff:100.64.0.1", false, false, false),
+            ("::ffff:192.0.0.9", true, false, false),
+        ];
+
+        for (s, global, loopback, private) in cases {
+            let host = ip(s);
+            assert_eq!(host.is_global(), global, "{s}");
+            assert_eq!(host.is_loopback(), loopback, "{s}");
+            assert_eq!(host.is_private(), private, "{s}");
+        }
+
+        assert!(ip("::ffff:224.0.0.1").is_multicast());
+        assert!(ip("::ffff:169.254.0.1").is_link_local());
+        assert!(ip("::ffff:0.0.0.0").is_unspecified());
+    }
+
+    #[test]
+    fn special_use_domains() {
+        let cases = [
+            ("localhost", Some(SpecialUseDomain::Localhost)),
+            ("localhost.", Some(SpecialUseDomain::Localhost)),
+            ("foo.localhost.", Some(SpecialUseDomain::Localhost)),
+            ("Foo.LOCALHOST", Some(SpecialUseDomain::Localhost)),
+            ("notlocalhost", None),
+            ("localhost.com", None),
+            ("printer.local", Some(SpecialUseDomain::Local)),
+            ("local", Some(SpecialUseDomain::Local)),
+            ("notlocal", None),
+            ("local.example.net", Some(SpecialUseDomain::Example)),
+            ("abc.onion", Some(SpecialUseDomain::Onion)),
+            ("invalid", Some(SpecialUseDomain::Invalid)),
+            ("foo.test", Some(SpecialUseDomain::Test)),
+            ("example", Some(SpecialUseDomain::Example)),
+            ("www.example.com", Some(SpecialUseDomain::Example)),
+            ("example.org.", Some(SpecialUseDomain::Example)),
+            ("myexample.com", None),
+            ("corp.internal", Some(SpecialUseDomain::Internal)),
+            ("example.co", None),
+        ];
+
+        for (s, domain) in cases {
+            let host = Host::from(s);
+            assert_eq!(host.special_use_domain(), domain, "{s}");
+            assert_eq!(host.is_special_use_name(), domain.is_some(), "{s}");
+        }
+
+        assert!(Host::from("foo.localhost.").is_loopback());
+        assert!(!Host::from("localhost.example").is_loopback());
+        assert_eq!(ip("127.0.0.1").special_use_domain(), None);
+        assert!(!Host::from("example.com").is_global());
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{
+    parse::split_host_port, to_ascii_name, DnsName, HostPortPair, HostPortPairRef, HostRef,
+    ParseError, ZoneId,
+};
+use alloc::borrow::ToOwned;
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    hash::{Hash, Hasher},
+    net::{IpAddr, Ipv6Addr},
+    str::FromStr,
+};
+
+/// A [`HostPortPair`] that stores its DNS name in a [`DnsName`], for large tables of pairs.
+///
+/// Names of up to 22 bytes don't allocate, and cloning never copies a name. The pair is the same
+/// size as a [`HostPortPair`], and compares and hashes like one.
+#[derive(Debug, Clone)]
+pub struct CompactHostPortPair {
+    host: CompactHost,
+    port: u16,
+}
+
+#[derive(Debug, Clone)]
+enum CompactHost {
+    IpAddr(IpAddr),
+    ScopedIpv6(Ipv6Addr, ZoneId),
+    DnsName(DnsName),
+}
+
+impl CompactHostPortPair {
+    pub fn host(&self) -> HostRef<'_> {
+        match &self.host {
+            CompactHost::IpAddr(ip) => HostRef::IpAddr(*ip),
+            CompactHost::ScopedIpv6(ip, zone) => HostRef::ScopedIpv6(*ip, zone.as_borrowed()),
+            CompactHost::DnsName(name) => HostRef::DnsName(name),
+        }
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    pub fn as_borrowed(&self) -> HostPortPairRef<'_> {
+        HostPortPairRef {
+            host: self.host(),
+            port: self.port,
+        }
+    }
+
+    pub fn to_host_port_pair(&self) -> HostPortPair {
+        self.as_borrowed().to_owned()
+    }
+}
+
+impl PartialEq for CompactHostPortPair {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_borrowed() == other.as_borrowed()
+    }
+}
+
+impl Eq for CompactHostPortPair {}
+
+impl PartialEq<HostPortPair> for CompactHostPortPair {
+    fn eq(&self, other: &HostPortPair) -> bool {
+        self.as_borrowed() == other.as_borrowed()
+    }
+}
+
+impl PartialEq<CompactHostPortPair> for HostPortPair {
+    fn eq(&self, other: &CompactHostPortPair) -> bool {
+        other == self
+    }
+}
+
+impl Hash for CompactHostPortPair {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_borrowed().hash(state)
+    }
+}
+
+impl From<HostPortPairRef<'_>> for CompactHostPortPair {
+    fn from(pair: HostPortPairRef<'_>) -> Self {
+        let host = match pair.host {
+            HostRef::IpAddr(ip) => CompactHost::IpAddr(ip),
+            HostRef::ScopedIpv6(ip, zone) => CompactHost::ScopedIpv6(ip, zone.to_owned()),
+            // Like `HostRef::to_owned`, so that the pair equals its owned form.
+            HostRef::DnsName(name) if name.is_ascii() => CompactHost::DnsName(DnsName::from(name)),
+            HostRef::DnsName(name) => {
+                CompactHost::DnsName(DnsName::from(to_ascii_name(name.to_owned())))
+            }
+        };
+
+        CompactHostPortPair {
+            host,
+            port: pair.port,
+        }
+    }
+}
+
+impl From<&HostPortPair> for CompactHostPortPair {
+    fn from(pair: &HostPortPair) -> Self {
+        Self::from(pair.as_borrowed())
+    }
+}
+
+impl From<HostPortPair> for CompactHostPortPair {
+    fn from(pair: HostPortPair) -> Self {
+        Self::from(pair.as_borrowed())
+    }
+}
+
+impl From<CompactHostPortPair> for HostPortPair {
+    fn from(pair: CompactHostPortPair) -> Self {
+        pair.to_host_port_pair()
+    }
+}
+
+impl From<&CompactHostPortPair> for HostPortPair {
+    fn from(pair: &CompactHostPortPair) -> Self {
+        pair.to_host_port_pair()
+    }
+}
+
+impl TryFrom<&str> for CompactHostPortPair {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let (host, port) = split_host_port(s)?;
+        Ok(Self::from(HostPortPairRef { host, port }))
+    }
+}
+
+impl FromStr for CompactHostPortPair {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl Display for CompactHostPortPair {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.as_borrowed().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn matches_host_port_pair() {
+        for s in [
+            "api.example.com:443",
+            "service-7.internal.eu-west-1.example.com:443",
+            "192.0.2.1:80",
+            "[fe80::1%25eth0]:22",
+        ] {
+            let pair = HostPortPair::try_from(s).unwrap();
+            let compact = CompactHostPortPair::try_from(s).unwrap();
+
+            assert_eq!(compact, pair);
+            assert_eq!(compact.to_string(), s);
+            assert_eq!(HostPortPair::from(compact.clone()), pair);
+            assert_eq!(CompactHostPortPair::from(&pair), compact);
+        }
+
+        let compact = CompactHostPortPair::try_from("Example.COM.:80").unwrap();
+        assert_eq!(compact, HostPortPair::try_from("example.com:80").unwrap());
+    }
+
+    #[test]
+    fn compact_layout() {
+        assert_eq!(
+            core::mem::size_of::<CompactHostPortPair>(),
+            core::mem::size_of::<HostPortPair>()
+        );
+    }
+}
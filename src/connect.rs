@@ -0,0 +1,277 @@
+use crate::{resolve::ResolveError, HostPortPair};
+use std::{
+    io::Result as IoResult,
+    net::{SocketAddr, TcpStream, ToSocketAddrs},
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+use std::io::{Error as IoError, ErrorKind};
+
+/// Connects to the first reachable address with Happy Eyeballs (RFC 8305).
+///
+/// Attempts alternate between address families, starting with the family of the first address,
+/// and a new attempt starts whenever the previous one fails or the attempt delay passes. The first
+/// connection to succeed wins.
+#[derive(Debug, Clone, Copy)]
+pub struct HappyEyeballs {
+    attempt_delay: Duration,
+    connect_timeout: Option<Duration>,
+}
+
+impl HappyEyeballs {
+    /// Uses the recommended attempt delay of 250 milliseconds and no connect timeout.
+    pub fn new() -> Self {
+        HappyEyeballs {
+            attempt_delay: Duration::from_millis(250),
+            connect_timeout: None,
+        }
+    }
+
+    pub fn with_attempt_delay(mut self, attempt_delay: Duration) -> Self {
+        self.attempt_delay = attempt_delay;
+        self
+    }
+
+    /// Limits how long each attempt may take.
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Connects on background threads, returning the stream and the address it connected to.
+    ///
+    /// Blocking connects can't be cancelled, so losing attempts keep running until they finish and
+    /// are then closed. Set a connect timeout to bound them.
+    pub fn connect(
+        &self,
+        addrs: impl IntoIterator<Item = SocketAddr>,
+    ) -> IoResult<(TcpStream, SocketAddr)> {
+        let mut addrs = interleave(addrs).into_iter().peekable();
+        let (tx, rx) = mpsc::channel();
+        let mut pending = 0;
+        let mut last_error = None;
+
+        loop {
+            if let Some(addr) = addrs.next() {
+                let tx = tx.clone();
+                let timeout = self.connect_timeout;
+
+                thread::spawn(move || {
+                    let stream = match timeout {
+                        Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                        None => TcpStream::connect(addr),
+                    };
+
+                    // The receiver is gone if another attempt already won, which drops the stream.
+                    let _ = tx.send(stream.map(|stream| (stream, addr)));
+                });
+
+                pending += 1;
+            }
+
+            if pending == 0 {
+                return Err(last_error.unwrap_or_else(|| ResolveError::NoRecords.into()));
+            }
+
+            let result = if addrs.peek().is_some() {
+                match rx.recv_timeout(self.attempt_delay) {
+                    Ok(result) => result,
+                    Err(_) => continue,
+                }
+            } else {
+                rx.recv().expect("a sender is held by this function")
+            };
+
+            pending -= 1;
+
+            match result {
+                Ok(connected) => return Ok(connected),
+                Err(err) => last_error = Some(err),
+            }
+        }
+    }
+
+    /// Connects with `tokio`, returning the stream and the address it connected to. Losing attempts
+    /// are cancelled.
+    #[cfg(feature = "tokio")]
+    pub async fn connect_async(
+        &self,
+        addrs: impl IntoIterator<Item = SocketAddr>,
+    ) -> IoResult<(tokio::net::TcpStream, SocketAddr)> {
+        let mut addrs = interleave(addrs).into_iter().peekable();
+        let mut attempts = tokio::task::JoinSet::new();
+        let mut last_error = None;
+
+        loop {
+            if let Some(addr) = addrs.next() {
+                let timeout = self.connect_timeout;
+
+                attempts.spawn(async move {
+                    let connect = tokio::net::TcpStream::connect(addr);
+
+                    let stream = match timeout {
+                        Some(timeout) => match tokio::time::timeout(timeout, connect).await {
+                            Ok(stream) => stream,
+                            Err(_) => Err(IoError::from(ErrorKind::TimedOut)),
+                        },
+                        None => connect.await,
+                    };
+
+                    stream.map(|stream| (stream, addr))
+                });
+            }
+
+            if attempts.is_empty() {
+                return Err(last_error.unwrap_or_else(|| ResolveError::NoRecords.into()));
+            }
+
+            let result = if addrs.peek().is_some() {
+                match tokio::time::timeout(self.attempt_delay, attempts.join_next()).await {
+                    Ok(result) => result,
+                    Err(_) => continue,
+                }
+            } else {
+                attempts.join_next().await
+            };
+
+            // Dropping `attempts` on return aborts the attempts that are still running.
+            match result.expect("the set of attempts is not empty") {
+                Ok(Ok(connected)) => return Ok(connected),
+                Ok(Err(err)) => last_error = Some(err),
+                Err(err) => last_error = Some(err.into()),
+            }
+        }
+    }
+}
+
+impl Default for HappyEyeballs {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HostPortPair {
+    /// Resolves the pair with the system resolver and connects with [`HappyEyeballs`].
+    pub fn connect_tcp(&self) -> IoResult<(TcpStream, SocketAddr)> {
+        HappyEyeballs::new().connect(self.to_socket_addrs()?)
+    }
+
+    /// Resolves the pair with the system resolver and connects with [`HappyEyeballs`] on `tokio`.
+    #[cfg(feature = "tokio")]
+    pub async fn connect_tcp_async(&self) -> IoResult<(tokio::net::TcpStream, SocketAddr)> {
+        let addrs = self.resolve().await?;
+        HappyEyeballs::new().connect_async(addrs).await
+    }
+}
+
+// Alternates address families, starting with the family of the first address.
+fn interleave(addrs: impl IntoIterator<Item = SocketAddr>) -> Vec<SocketAddr> {
+    let addrs = addrs.into_iter().collect::<Vec<_>>();
+
+    let Some(first) = addrs.first() else {
+        return addrs;
+    };
+
+    let first_is_ipv6 = first.is_ipv6();
+    let mut interleaved = Vec::with_capacity(addrs.len());
+
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_is_ipv6);
+
+    let mut preferred = preferred.into_iter();
+    let mut other = other.into_iter();
+
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return interleaved,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    // A listener on an ephemeral loopback port, and an address on which connections are refused.
+    fn listeners() -> (TcpListener, SocketAddr) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let refused = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        (listener, refused)
+    }
+
+    #[test]
+    fn interleaves_families() {
+        let addrs = [
+            "[::1]:1",
+            "[::1]:2",
+            "[::1]:3",
+            "127.0.0.1:4",
+            "127.0.0.1:5",
+        ]
+        .map(|addr| addr.parse::<SocketAddr>().unwrap());
+
+        let ports = interleave(addrs)
+            .iter()
+            .map(SocketAddr::port)
+            .collect::<Vec<_>>();
+        assert_eq!(ports, [1, 4, 2, 5, 3]);
+    }
+
+    #[test]
+    fn connects_after_refused_attempt() {
+        let (listener, refused) = listeners();
+        let addr = listener.local_addr().unwrap();
+
+        let (stream, connected) = HappyEyeballs::new()
+            .with_attempt_delay(Duration::from_secs(10))
+            .with_connect_timeout(Duration::from_secs(5))
+            .connect([refused, addr])
+            .unwrap();
+
+        assert_eq!(connected, addr);
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+        assert_eq!(listener.accept().unwrap().0.local_addr().unwrap(), addr);
+    }
+
+    #[test]
+    fn reports_last_error() {
+        let (_, refused) = listeners();
+
+        let err = HappyEyeballs::new().connect([refused]).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+
+        assert!(HappyEyeballs::new().connect([]).is_err());
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn connects_async() {
+        let (listener, refused) = listeners();
+        let addr = listener.local_addr().unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+
+        let (stream, connected) = runtime
+            .block_on(
+                HappyEyeballs::new()
+                    .with_attempt_delay(Duration::from_secs(10))
+                    .connect_async([refused, addr]),
+            )
+            .unwrap();
+
+        assert_eq!(connected, addr);
+        assert_eq!(stream.peer_addr().unwrap(), addr);
+    }
+}
@@ -0,0 +1,208 @@
+use alloc::{borrow::ToOwned, string::String, sync::Arc};
+use core::{
+    borrow::Borrow,
+    fmt::{Debug, Display, Formatter, Result as FmtResult},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+const INLINE_CAP: usize = 22;
+
+/// A name stored compactly, as in [`CompactHostPortPair`](crate::CompactHostPortPair) and
+/// [`ZoneId::Name`](crate::ZoneId::Name).
+///
+/// Names of up to 22 bytes are stored inline without a heap allocation. Longer names are stored
+/// in a shared `Arc<str>`, so cloning never copies the name.
+#[derive(Clone)]
+pub struct DnsName(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Inline { len: u8, buf: [u8; INLINE_CAP] },
+    Shared(Arc<str>),
+}
+
+impl DnsName {
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            // SAFETY: `buf[..len]` is always copied from a `&str` in `From<&str>`.
+            Repr::Inline { len, buf } => unsafe {
+                core::str::from_utf8_unchecked(&buf[..*len as usize])
+            },
+            Repr::Shared(name) => name,
+        }
+    }
+
+    pub fn is_inline(&self) -> bool {
+        matches!(self.0, Repr::Inline { .. })
+    }
+}
+
+impl Deref for DnsName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for DnsName {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for DnsName {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for DnsName {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for DnsName {}
+
+impl PartialEq<str> for DnsName {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for DnsName {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<String> for DnsName {
+    fn eq(&self, other: &String) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl Hash for DnsName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl From<&str> for DnsName {
+    fn from(name: &str) -> Self {
+        if name.len() > INLINE_CAP {
+            return DnsName(Repr::Shared(Arc::from(name)));
+        }
+
+        let mut buf = [0; INLINE_CAP];
+        buf[..name.len()].copy_from_slice(name.as_bytes());
+
+        DnsName(Repr::Inline {
+            len: name.len() as u8,
+            buf,
+        })
+    }
+}
+
+impl From<&String> for DnsName {
+    fn from(name: &String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+impl From<String> for DnsName {
+    fn from(name: String) -> Self {
+        Self::from(name.as_str())
+    }
+}
+
+impl From<Arc<str>> for DnsName {
+    fn from(name: Arc<str>) -> Self {
+        if name.len() > INLINE_CAP {
+            DnsName(Repr::Shared(name))
+        } else {
+            Self::from(&*name)
+        }
+    }
+}
+
+impl From<DnsName> for String {
+    fn from(name: DnsName) -> Self {
+        name.as_str().to_owned()
+    }
+}
+
+impl Debug for DnsName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for DnsName {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde {
+    use super::*;
+    use ::serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    impl Serialize for DnsName {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            ser.serialize_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for DnsName {
+        fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            let s = String::deserialize(de)?;
+            Ok(Self::from(s))
+        }
+    }
+}
+
+// `DnsName` archives as a plain `ArchivedString`, like the `String` that it stands in for.
+#[cfg(feature = "rkyv")]
+mod rkyv {
+    use super::*;
+    use ::rkyv::{
+        rancor::{Fallible, Source},
+        string::{ArchivedString, StringResolver},
+        Archive, Deserialize, Place, Serialize, SerializeUnsized,
+    };
+
+    impl Archive for DnsName {
+        type Archived = ArchivedString;
+        type Resolver = StringResolver;
+
+        fn resolve(&self, resolver: Self::Resolver, out: Place<Self::Archived>) {
+            ArchivedString::resolve_from_str(self, resolver, out);
+        }
+    }
+
+    impl<S: Fallible + ?Sized> Serialize<S> for DnsName
+    where
+        S::Error: Source,
+        str: SerializeUnsized<S>,
+    {
+        fn serialize(&self, serializer: &mut S) -> Result<Self::Resolver, S::Error> {
+            ArchivedString::serialize_from_str(self, serializer)
+        }
+    }
+
+    impl PartialEq<DnsName> for ArchivedString {
+        fn eq(&self, other: &DnsName) -> bool {
+            self.as_str() == other.as_str()
+        }
+    }
+
+    impl<D: Fallible + ?Sized> Deserialize<DnsName, D> for ArchivedString {
+        fn deserialize(&self, _: &mut D) -> Result<DnsName, D::Error> {
+            Ok(DnsName::from(self.as_str()))
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use alloc::string::String;
+use core::ops::Range;
+use thiserror::Error;
+
+/// An error from parsing a host, a host-port pair, a URL, a network, a host pattern or a proxy.
+///
+/// The span is the byte range of the offending part of the input, so that tools can point at it.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{kind} at {}..{}", span.start, span.end)]
+pub struct ParseError {
+    kind: ParseErrorKind,
+    span: Range<usize>,
+}
+
+#[deprecated(note = "renamed to `ParseError`")]
+pub type HostPortPairError = ParseError;
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ParseErrorKind {
+    #[error("empty host")]
+    EmptyHost,
+    #[error("invalid character {0:?} in host")]
+    InvalidHostChar(char),
+    #[error("no port")]
+    NoPort,
+    #[error("empty port")]
+    EmptyPort,
+    #[error("port must not have a sign")]
+    SignedPort,
+    #[error("invalid character {0:?} in port")]
+    InvalidPortChar(char),
+    #[error("port is greater than 65535")]
+    PortOutOfRange,
+    #[error("unclosed bracket")]
+    UnclosedBracket,
+    #[error("unexpected trailing characters")]
+    TrailingGarbage,
+    #[error("invalid IPv6 literal")]
+    InvalidIpv6Literal,
+    #[error("invalid IPv6 zone identifier")]
+    InvalidZoneId,
+    #[error("IPv6 address must be enclosed in brackets")]
+    UnbracketedIpv6,
+    #[error("invalid DNS name: {0}")]
+    InvalidDnsName(DnsNameError),
+    #[error("invalid URL scheme")]
+    InvalidScheme,
+    #[error("URL has no authority")]
+    NoAuthority,
+    #[error("no default port for scheme {0:?}")]
+    UnknownScheme(String),
+    #[error("invalid percent-encoding in host")]
+    InvalidPercentEncoding,
+    #[error("invalid IP address")]
+    InvalidIpAddr,
+    #[error("no prefix length")]
+    NoPrefixLen,
+    #[error("invalid prefix length")]
+    InvalidPrefixLen,
+    #[error("domain suffix is not a DNS name")]
+    InvalidDomainSuffix,
+    #[error("unsupported proxy scheme {0:?}")]
+    UnsupportedProxyScheme(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum DnsNameError {
+    #[error("name is empty")]
+    Empty,
+    #[error("name is longer than 253 characters")]
+    TooLong,
+    #[error("label is empty")]
+    EmptyLabel,
+    #[error("label is longer than 63 characters")]
+    LabelTooLong,
+    #[error("invalid character {0:?}")]
+    InvalidChar(char),
+    #[error("label starts with a hyphen")]
+    LeadingHyphen,
+    #[error("label ends with a hyphen")]
+    TrailingHyphen,
+    #[error("top-level label is all digits")]
+    NumericTopLevel,
+    #[error("invalid internationalized domain name")]
+    Idna,
+}
+
+/// An error from converting a [`HostPortPair`](crate::HostPortPair) to a socket address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum SocketAddrError {
+    #[error("host is a DNS name")]
+    DnsName,
+    #[error("host is an address of the other family")]
+    AddressFamily,
+    #[error("unknown IPv6 zone")]
+    UnknownZone,
+}
+
+/// An error from encoding or decoding a SOCKS5 address (RFC 1928).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum Socks5AddrError {
+    #[error("address is truncated")]
+    Truncated,
+    #[error("unknown address type {0:#04x}")]
+    UnknownAddrType(u8),
+    #[error("domain name is empty")]
+    EmptyDomain,
+    #[error("domain name is longer than 255 bytes")]
+    DomainTooLong,
+    #[error("domain name is not valid UTF-8")]
+    InvalidDomain,
+}
+
+impl ParseError {
+    pub(crate) fn new(kind: ParseErrorKind, span: Range<usize>) -> Self {
+        ParseError { kind, span }
+    }
+
+    pub fn kind(&self) -> &ParseErrorKind {
+        &self.kind
+    }
+
+    pub fn span(&self) -> Range<usize> {
+        self.span.clone()
+    }
+
+    #[cfg(feature = "idna")]
+    pub(crate) fn with_span(mut self, span: Range<usize>) -> Self {
+        self.span = span;
+        self
+    }
+
+    // Rebases the span of an error found in a substring that starts at `offset`.
+    pub(crate) fn offset(mut self, offset: usize) -> Self {
+        self.span = self.span.start + offset..self.span.end + offset;
+        self
+    }
+}
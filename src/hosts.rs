@@ -0,0 +1,184 @@
+use crate::Host;
+use alloc::vec::Vec;
+use core::net::IpAddr;
+
+/// The entries of a hosts file in the `/etc/hosts` format.
+///
+/// Each line holds an address followed by a canonical name and any aliases. Comments start with
+/// `#`. Like the system resolver, lines without a valid address are skipped, as are names that are
+/// IP addresses. Zones of IPv6 addresses, as in macOS's `fe80::1%lo0 localhost`, are dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HostsFile {
+    entries: Vec<HostsEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostsEntry {
+    ip: IpAddr,
+    names: Vec<Host>,
+}
+
+impl HostsFile {
+    pub fn parse(text: &str) -> Self {
+        let entries = text.lines().filter_map(HostsEntry::parse).collect();
+        HostsFile { entries }
+    }
+
+    /// Reads and parses a hosts file, such as `/etc/hosts`.
+    #[cfg(feature = "std")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        std::fs::read_to_string(path).map(|text| Self::parse(&text))
+    }
+
+    pub fn entries(&self) -> &[HostsEntry] {
+        &self.entries
+    }
+
+    /// Returns the addresses of `host` in file order.
+    pub fn lookup(&self, host: &Host) -> Vec<IpAddr> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.names.contains(host))
+            .map(|entry| entry.ip)
+            .collect()
+    }
+
+    /// Returns the names of an address in file order, canonical names first within each line.
+    pub fn reverse(&self, ip: IpAddr) -> Vec<&Host> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.ip == ip)
+            .flat_map(|entry| &entry.names)
+            .collect()
+    }
+}
+
+impl HostsEntry {
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.split('#').next().unwrap_or_default();
+        let mut fields = line.split_whitespace();
+        let ip = fields.next()?;
+
+        let ip = match ip.split_once('%') {
+            Some((ip, _)) => IpAddr::V6(ip.parse().ok()?),
+            None => ip.parse().ok()?,
+        };
+
+        let names = fields
+            .map(Host::from)
+            .filter(|name| name.is_dns_name())
+            .collect::<Vec<_>>();
+
+        if names.is_empty() {
+            return None;
+        }
+
+        Some(HostsEntry { ip, names })
+    }
+
+    pub fn ip(&self) -> IpAddr {
+        self.ip
+    }
+
+    pub fn canonical_name(&self) -> &Host {
+        &self.names[0]
+    }
+
+    pub fn aliases(&self) -> &[Host] {
+        &self.names[1..]
+    }
+
+    /// Returns the canonical name followed by the aliases.
+    pub fn names(&self) -> &[Host] {
+        &self.names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOSTS: &str = "\
+# The default hosts file.
+127.0.0.1\tlocalhost
+::1 localhost ip6-localhost ip6-loopback
+fe80::1%lo0 localhost
+
+192.0.2.10  web.example  www.example  # The web server.
+192.0.2.11 db.example 192.0.2.12
+not-an-address bogus.example
+192.0.2.13
+  192.0.2.10 WEB2.example
+";
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn entries() {
+        let hosts = HostsFile::parse(HOSTS);
+        let ips = hosts
+            .entries()
+            .iter()
+            .map(HostsEntry::ip)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            ips,
+            [
+                ip("127.0.0.1"),
+                ip("::1"),
+                ip("fe80::1"),
+                ip("192.0.2.10"),
+                ip("192.0.2.11"),
+                ip("192.0.2.10"),
+            ]
+        );
+
+        let entry = &hosts.entries()[1];
+        assert_eq!(entry.canonical_name(), &Host::from("localhost"));
+        assert_eq!(
+            entry.aliases(),
+            [Host::from("ip6-localhost"), Host::from("ip6-loopback")]
+        );
+
+        // Addresses in the name position are skipped.
+        assert_eq!(hosts.entries()[4].names(), [Host::from("db.example")]);
+    }
+
+    #[test]
+    fn lookup() {
+        let hosts = HostsFile::parse(HOSTS);
+
+        assert_eq!(
+            hosts.lookup(&Host::from("localhost")),
+            [ip("127.0.0.1"), ip("::1"), ip("fe80::1")]
+        );
+        assert_eq!(
+            hosts.lookup(&Host::from("WWW.example.")),
+            [ip("192.0.2.10")]
+        );
+        assert_eq!(
+            hosts.lookup(&Host::from("web2.example")),
+            [ip("192.0.2.10")]
+        );
+        assert!(hosts.lookup(&Host::from("bogus.example")).is_empty());
+        assert!(hosts.lookup(&Host::from("The")).is_empty());
+    }
+
+    #[test]
+    fn reverse() {
+        let hosts = HostsFile::parse(HOSTS);
+
+        assert_eq!(
+            hosts.reverse(ip("192.0.2.10")),
+            [
+                &Host::from("web.example"),
+                &Host::from("www.example"),
+                &Host::from("WEB2.example"),
+            ]
+        );
+        assert!(hosts.reverse(ip("192.0.2.13")).is_empty());
+    }
+}
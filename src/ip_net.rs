@@ -0,0 +1,213 @@
+use crate::{ParseError, ParseErrorKind};
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
+
+/// An IPv4 network in CIDR notation, such as `10.0.0.0/8`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ipv4Net {
+    addr: Ipv4Addr,
+    prefix_len: u8,
+}
+
+/// An IPv6 network in CIDR notation, such as `fc00::/7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Ipv6Net {
+    addr: Ipv6Addr,
+    prefix_len: u8,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IpNet {
+    V4(Ipv4Net),
+    V6(Ipv6Net),
+}
+
+impl Ipv4Net {
+    /// Returns `None` if `prefix_len` is greater than 32. Host bits in `addr` are kept.
+    pub fn new(addr: Ipv4Addr, prefix_len: u8) -> Option<Self> {
+        (prefix_len <= 32).then_some(Ipv4Net { addr, prefix_len })
+    }
+
+    pub fn addr(&self) -> Ipv4Addr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns the address with the host bits cleared.
+    pub fn network(&self) -> Ipv4Addr {
+        Ipv4Addr::from(u32::from(self.addr) & self.mask())
+    }
+
+    pub fn contains(&self, ip: Ipv4Addr) -> bool {
+        u32::from(ip) & self.mask() == u32::from(self.network())
+    }
+
+    fn mask(&self) -> u32 {
+        u32::MAX
+            .checked_shl(32 - u32::from(self.prefix_len))
+            .unwrap_or(0)
+    }
+}
+
+impl Ipv6Net {
+    /// Returns `None` if `prefix_len` is greater than 128. Host bits in `addr` are kept.
+    pub fn new(addr: Ipv6Addr, prefix_len: u8) -> Option<Self> {
+        (prefix_len <= 128).then_some(Ipv6Net { addr, prefix_len })
+    }
+
+    pub fn addr(&self) -> Ipv6Addr {
+        self.addr
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.prefix_len
+    }
+
+    /// Returns the address with the host bits cleared.
+    pub fn network(&self) -> Ipv6Addr {
+        Ipv6Addr::from(u128::from(self.addr) & self.mask())
+    }
+
+    pub fn contains(&self, ip: Ipv6Addr) -> bool {
+        u128::from(ip) & self.mask() == u128::from(self.network())
+    }
+
+    fn mask(&self) -> u128 {
+        u128::MAX
+            .checked_shl(128 - u32::from(self.prefix_len))
+            .unwrap_or(0)
+    }
+}
+
+impl IpNet {
+    /// Returns `None` if `prefix_len` is longer than the address.
+    pub fn new(addr: IpAddr, prefix_len: u8) -> Option<Self> {
+        match addr {
+            IpAddr::V4(addr) => Ipv4Net::new(addr, prefix_len).map(IpNet::V4),
+            IpAddr::V6(addr) => Ipv6Net::new(addr, prefix_len).map(IpNet::V6),
+        }
+    }
+
+    pub fn addr(&self) -> IpAddr {
+        match self {
+            IpNet::V4(net) => IpAddr::V4(net.addr),
+            IpNet::V6(net) => IpAddr::V6(net.addr),
+        }
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        match self {
+            IpNet::V4(net) => net.prefix_len,
+            IpNet::V6(net) => net.prefix_len,
+        }
+    }
+
+    pub fn network(&self) -> IpAddr {
+        match self {
+            IpNet::V4(net) => IpAddr::V4(net.network()),
+            IpNet::V6(net) => IpAddr::V6(net.network()),
+        }
+    }
+
+    /// Returns whether `ip` is in the network. Addresses of the other family are never contained.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self, ip) {
+            (IpNet::V4(net), IpAddr::V4(ip)) => net.contains(ip),
+            (IpNet::V6(net), IpAddr::V6(ip)) => net.contains(ip),
+            _ => false,
+        }
+    }
+}
+
+impl From<Ipv4Net> for IpNet {
+    fn from(net: Ipv4Net) -> Self {
+        IpNet::V4(net)
+    }
+}
+
+impl From<Ipv6Net> for IpNet {
+    fn from(net: Ipv6Net) -> Self {
+        IpNet::V6(net)
+    }
+}
+
+impl Display for Ipv4Net {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl Display for Ipv6Net {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "{}/{}", self.addr, self.prefix_len)
+    }
+}
+
+impl Display for IpNet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            IpNet::V4(net) => net.fmt(f),
+            IpNet::V6(net) => net.fmt(f),
+        }
+    }
+}
+
+impl FromStr for Ipv4Net {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = split_net(s)?;
+        Ipv4Net::new(addr, prefix_len).ok_or_else(|| prefix_len_error(s))
+    }
+}
+
+impl FromStr for Ipv6Net {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = split_net(s)?;
+        Ipv6Net::new(addr, prefix_len).ok_or_else(|| prefix_len_error(s))
+    }
+}
+
+impl FromStr for IpNet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix_len) = split_net(s)?;
+        IpNet::new(addr, prefix_len).ok_or_else(|| prefix_len_error(s))
+    }
+}
+
+// Splits `addr/prefix_len`, leaving the range check of the prefix length to the caller.
+fn split_net<A: FromStr>(s: &str) -> Result<(A, u8), ParseError> {
+    let Some((addr, prefix_len)) = s.split_once('/') else {
+        return Err(ParseError::new(
+            ParseErrorKind::NoPrefixLen,
+            s.len()..s.len(),
+        ));
+    };
+
+    let addr = addr
+        .parse()
+        .map_err(|_| ParseError::new(ParseErrorKind::InvalidIpAddr, 0..addr.len()))?;
+
+    // `u8::from_str` accepts a leading `+`.
+    if prefix_len.is_empty() || !prefix_len.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(prefix_len_error(s));
+    }
+
+    let prefix_len = prefix_len.parse().map_err(|_| prefix_len_error(s))?;
+    Ok((addr, prefix_len))
+}
+
+fn prefix_len_error(s: &str) -> ParseError {
+    let start = s.find('/').map_or(s.len(), |slash| slash + 1);
+    ParseError::new(ParseErrorKind::InvalidPrefixLen, start..s.len())
+}
@@ -1,17 +1,57 @@
 #![doc = include_str!("../README.md")]
+#![cfg_attr(not(feature = "std"), no_std)]
 
-use std::{
+extern crate alloc;
+
+use crate::parse::{parse_ip_host, split_host_maybe_port, split_host_port};
+use alloc::{borrow::ToOwned, string::String};
+use core::{
     fmt::{Display, Formatter, Result as FmtResult},
+    hash::{Hash, Hasher},
     net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6},
-    num::ParseIntError,
     str::FromStr,
 };
-use thiserror::Error;
 
-pub use crate::host_port_pair::{Host, HostPortPair};
+#[allow(deprecated)]
+pub use crate::error::HostPortPairError;
+pub use crate::{
+    borrowed::{HostPortPairRef, HostRef, ZoneIdRef},
+    classify::SpecialUseDomain,
+    compact::CompactHostPortPair,
+    dns_name::DnsName,
+    error::{DnsNameError, ParseError, ParseErrorKind, SocketAddrError, Socks5AddrError},
+    host_port_pair::{Host, HostMaybePort, HostPortPair, ZoneId},
+    ip_net::{IpNet, Ipv4Net, Ipv6Net},
+    pattern::HostPattern,
+};
+
+#[cfg(feature = "std")]
+pub mod connect;
+pub mod hosts;
+pub mod policy;
+pub mod proxy;
+pub mod proxy_protocol;
+#[cfg(feature = "std")]
+pub mod resolve;
+pub mod sort;
+pub mod url;
+
+mod borrowed;
+mod classify;
+mod compact;
+mod dns_name;
+mod error;
+mod ip_net;
+#[cfg(feature = "std")]
+mod net;
+mod parse;
+mod pattern;
+mod socks5;
 
 mod host_port_pair {
-    use std::net::IpAddr;
+    use crate::DnsName;
+    use alloc::{boxed::Box, string::String};
+    use core::net::{IpAddr, Ipv6Addr};
 
     #[cfg_attr(
         feature = "rkyv",
@@ -30,21 +70,56 @@ mod host_port_pair {
         rkyv(derive(Debug, Hash), compare(PartialEq))
     )]
     #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub struct HostMaybePort {
+        pub(crate) host: Host,
+        pub(crate) port: Option<u16>,
+    }
+
+    #[cfg_attr(
+        feature = "rkyv",
+        derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+        rkyv(derive(Debug))
+    )]
+    #[derive(Debug, Clone)]
     pub enum Host {
         IpAddr(IpAddr),
+        ScopedIpv6(Ipv6Addr, ZoneId),
         DnsName(String),
     }
-}
 
-#[derive(Debug, Error)]
-pub enum HostPortPairError {
-    #[error("no port")]
-    NoPort,
-    #[error("invalid port: {0}")]
-    ParsePort(#[from] ParseIntError),
+    #[cfg_attr(
+        feature = "rkyv",
+        derive(rkyv::Archive, rkyv::Serialize, rkyv::Deserialize),
+        rkyv(derive(Debug, Hash), compare(PartialEq))
+    )]
+    #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+    pub enum ZoneId {
+        Index(u32),
+        // Boxed so that `Host` stays 32 bytes, as named zones are rare.
+        Name(Box<DnsName>),
+    }
 }
 
 impl HostPortPair {
+    /// Converts an IP host to a socket address without a DNS lookup.
+    ///
+    /// Returns `None` for DNS names and for IPv6 zones whose interface can't be found.
+    pub fn as_socket_addr(&self) -> Option<SocketAddr> {
+        self.as_borrowed().as_socket_addr()
+    }
+
+    /// Parses `host:port`, falling back to `default_port` if the port is omitted.
+    pub fn parse_with_default_port(s: &str, default_port: u16) -> Result<Self, ParseError> {
+        HostMaybePort::try_from(s).map(|host| host.with_default_port(default_port))
+    }
+
+    pub fn as_borrowed(&self) -> HostPortPairRef<'_> {
+        HostPortPairRef {
+            host: self.host.as_borrowed(),
+            port: self.port,
+        }
+    }
+
     pub fn host(&self) -> &Host {
         &self.host
     }
@@ -60,16 +135,157 @@ impl HostPortPair {
     pub fn port_mut(&mut self) -> &mut u16 {
         &mut self.port
     }
+
+    pub fn normalized(&self) -> Self {
+        let mut pair = self.clone();
+        pair.host.canonicalize();
+        pair
+    }
+}
+
+impl HostMaybePort {
+    pub fn host(&self) -> &Host {
+        &self.host
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    pub fn host_mut(&mut self) -> &mut Host {
+        &mut self.host
+    }
+
+    pub fn port_mut(&mut self) -> &mut Option<u16> {
+        &mut self.port
+    }
+
+    pub fn with_default_port(self, default_port: u16) -> HostPortPair {
+        HostPortPair {
+            host: self.host,
+            port: self.port.unwrap_or(default_port),
+        }
+    }
 }
 
 impl Host {
+    /// Parses an IP address or an RFC 1123 hostname, rejecting anything else.
+    ///
+    /// Unlike the `From` conversions, which treat any non-IP string as a DNS name, this validates
+    /// the name's labels, length and characters.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        #[cfg(feature = "idna")]
+        if !s.is_ascii() && !s.starts_with('[') {
+            let name = idna_to_ascii(s).ok_or_else(|| {
+                ParseError::new(
+                    ParseErrorKind::InvalidDnsName(DnsNameError::Idna),
+                    0..s.len(),
+                )
+            })?;
+
+            // Spans into the converted name are meaningless, so errors cover the whole input.
+            crate::parse::validate_dns_name(&name).map_err(|err| err.with_span(0..s.len()))?;
+            return Ok(Host::DnsName(name));
+        }
+
+        HostRef::parse(s).map(|host| host.to_owned())
+    }
+
+    pub fn as_borrowed(&self) -> HostRef<'_> {
+        match self {
+            Host::IpAddr(ip) => HostRef::IpAddr(*ip),
+            Host::ScopedIpv6(ip, zone) => HostRef::ScopedIpv6(*ip, zone.as_borrowed()),
+            Host::DnsName(name) => HostRef::DnsName(name),
+        }
+    }
+
+    /// Returns the host with A-labels (`xn--`) of DNS names decoded back to Unicode.
+    #[cfg(feature = "idna")]
+    pub fn to_unicode(&self) -> alloc::borrow::Cow<'_, str> {
+        use alloc::{borrow::Cow, string::ToString};
+
+        match self {
+            Host::DnsName(name) if has_a_label(name) => Cow::Owned(idna::domain_to_unicode(name).0),
+            Host::DnsName(name) => Cow::Borrowed(name),
+            host => Cow::Owned(host.to_string()),
+        }
+    }
+
+    /// Lowercases a DNS name and strips its trailing root dot.
+    pub fn canonicalize(&mut self) {
+        if let Host::DnsName(name) = self {
+            name.truncate(trim_root(name).len());
+            name.make_ascii_lowercase();
+        }
+    }
+
     pub fn is_ip_address(&self) -> bool {
-        matches!(self, Host::IpAddr(_))
+        matches!(self, Host::IpAddr(_) | Host::ScopedIpv6(..))
     }
 
     pub fn is_dns_name(&self) -> bool {
         matches!(self, Host::DnsName(_))
     }
+
+    pub fn ip_addr(&self) -> Option<IpAddr> {
+        match self {
+            Host::IpAddr(ip) => Some(*ip),
+            Host::ScopedIpv6(ip, _) => Some(IpAddr::V6(*ip)),
+            Host::DnsName(_) => None,
+        }
+    }
+
+    pub fn zone_id(&self) -> Option<&ZoneId> {
+        match self {
+            Host::ScopedIpv6(_, zone) => Some(zone),
+            _ => None,
+        }
+    }
+}
+
+impl ZoneId {
+    /// Returns the numeric scope ID, looking up the interface index for named zones.
+    pub fn index(&self) -> Option<u32> {
+        self.as_borrowed().index()
+    }
+
+    pub fn as_borrowed(&self) -> ZoneIdRef<'_> {
+        match self {
+            ZoneId::Index(index) => ZoneIdRef::Index(*index),
+            ZoneId::Name(name) => ZoneIdRef::Name(name),
+        }
+    }
+}
+
+#[cfg(all(feature = "std", unix))]
+fn interface_index(name: &str) -> Option<u32> {
+    let name = std::ffi::CString::new(name).ok()?;
+    // SAFETY: `name` is a valid NUL-terminated string that outlives the call.
+    match unsafe { libc::if_nametoindex(name.as_ptr()) } {
+        0 => None,
+        index => Some(index),
+    }
+}
+
+#[cfg(not(all(feature = "std", unix)))]
+fn interface_index(_name: &str) -> Option<u32> {
+    None
+}
+
+// DNS names compare ASCII case-insensitively and ignore a trailing root dot, matching the
+// resolution semantics of the name. The archived types hash and compare identically.
+impl PartialEq for Host {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_borrowed() == other.as_borrowed()
+    }
+}
+
+impl Eq for Host {}
+
+impl Hash for Host {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_borrowed().hash(state)
+    }
 }
 
 impl From<IpAddr> for Host {
@@ -92,27 +308,27 @@ impl From<Ipv6Addr> for Host {
 
 impl From<String> for Host {
     fn from(host: String) -> Self {
-        match host.parse() {
-            Ok(ip) => Host::IpAddr(ip),
-            Err(_) => Host::DnsName(host),
+        match parse_ip_host(&host) {
+            Some(ip) => ip.to_owned(),
+            None => Host::DnsName(to_ascii_name(host)),
         }
     }
 }
 
 impl From<&String> for Host {
     fn from(host: &String) -> Self {
-        match host.parse() {
-            Ok(ip) => Host::IpAddr(ip),
-            Err(_) => Host::DnsName(host.clone()),
+        match parse_ip_host(host) {
+            Some(ip) => ip.to_owned(),
+            None => Host::DnsName(to_ascii_name(host.clone())),
         }
     }
 }
 
 impl From<&str> for Host {
     fn from(host: &str) -> Self {
-        match host.parse() {
-            Ok(ip) => Host::IpAddr(ip),
-            Err(_) => Host::DnsName(host.to_owned()),
+        match parse_ip_host(host) {
+            Some(ip) => ip.to_owned(),
+            None => Host::DnsName(to_ascii_name(host.to_owned())),
         }
     }
 }
@@ -128,9 +344,9 @@ impl<T: Into<Host>> From<(T, u16)> for HostPortPair {
 
 impl From<SocketAddr> for HostPortPair {
     fn from(addr: SocketAddr) -> Self {
-        HostPortPair {
-            host: Host::IpAddr(addr.ip()),
-            port: addr.port(),
+        match addr {
+            SocketAddr::V4(addr) => addr.into(),
+            SocketAddr::V6(addr) => addr.into(),
         }
     }
 }
@@ -144,69 +360,192 @@ impl From<SocketAddrV4> for HostPortPair {
     }
 }
 
+/// A nonzero scope ID becomes a numeric zone. The flow label is dropped, as a host-port pair has no
+/// place for it.
 impl From<SocketAddrV6> for HostPortPair {
     fn from(addr: SocketAddrV6) -> Self {
+        let host = match addr.scope_id() {
+            0 => Host::from(*addr.ip()),
+            scope_id => Host::ScopedIpv6(*addr.ip(), ZoneId::Index(scope_id)),
+        };
+
         HostPortPair {
-            host: Host::from(*addr.ip()),
+            host,
             port: addr.port(),
         }
     }
 }
 
+impl TryFrom<HostPortPair> for SocketAddr {
+    type Error = SocketAddrError;
+
+    fn try_from(pair: HostPortPair) -> Result<Self, Self::Error> {
+        match pair.host {
+            Host::DnsName(_) => Err(SocketAddrError::DnsName),
+            _ => pair.as_socket_addr().ok_or(SocketAddrError::UnknownZone),
+        }
+    }
+}
+
+impl TryFrom<HostPortPair> for SocketAddrV4 {
+    type Error = SocketAddrError;
+
+    fn try_from(pair: HostPortPair) -> Result<Self, Self::Error> {
+        match SocketAddr::try_from(pair)? {
+            SocketAddr::V4(addr) => Ok(addr),
+            SocketAddr::V6(_) => Err(SocketAddrError::AddressFamily),
+        }
+    }
+}
+
+impl TryFrom<HostPortPair> for SocketAddrV6 {
+    type Error = SocketAddrError;
+
+    fn try_from(pair: HostPortPair) -> Result<Self, Self::Error> {
+        match SocketAddr::try_from(pair)? {
+            SocketAddr::V6(addr) => Ok(addr),
+            SocketAddr::V4(_) => Err(SocketAddrError::AddressFamily),
+        }
+    }
+}
+
+// A DNS name never equals a socket address, since that would need a lookup. For the same reason,
+// only numeric zones are compared; converting a zone name to an index is left to `TryFrom` and
+// `as_socket_addr`.
+impl PartialEq<SocketAddr> for HostPortPair {
+    fn eq(&self, other: &SocketAddr) -> bool {
+        match (&self.host, other) {
+            (Host::IpAddr(ip), _) => SocketAddr::new(*ip, self.port) == *other,
+            (Host::ScopedIpv6(ip, ZoneId::Index(index)), SocketAddr::V6(addr)) => {
+                SocketAddrV6::new(*ip, self.port, 0, *index) == *addr
+            }
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<HostPortPair> for SocketAddr {
+    fn eq(&self, other: &HostPortPair) -> bool {
+        other == self
+    }
+}
+
+impl From<Host> for HostMaybePort {
+    fn from(host: Host) -> Self {
+        HostMaybePort { host, port: None }
+    }
+}
+
+impl From<HostPortPair> for HostMaybePort {
+    fn from(pair: HostPortPair) -> Self {
+        HostMaybePort {
+            host: pair.host,
+            port: Some(pair.port),
+        }
+    }
+}
+
+impl<T: Into<Host>> From<(T, Option<u16>)> for HostMaybePort {
+    fn from((host, port): (T, Option<u16>)) -> Self {
+        HostMaybePort {
+            host: host.into(),
+            port,
+        }
+    }
+}
+
 impl TryFrom<String> for HostPortPair {
-    type Error = HostPortPairError;
+    type Error = ParseError;
 
     fn try_from(mut s: String) -> Result<Self, Self::Error> {
-        let Some((host, port)) = s.rsplit_once(':') else {
-            return Err(HostPortPairError::NoPort);
+        let (host, port) = match split_host_port(&s)? {
+            (HostRef::DnsName(name), port) => {
+                s.truncate(name.len());
+                (Host::DnsName(to_ascii_name(s)), port)
+            }
+            (host, port) => (host.to_owned(), port),
         };
 
-        let port = port.parse()?;
-        s.truncate(host.len());
-
-        Ok(HostPortPair {
-            host: Host::from(s),
-            port,
-        })
+        Ok(HostPortPair { host, port })
     }
 }
 
 impl TryFrom<&String> for HostPortPair {
-    type Error = HostPortPairError;
+    type Error = ParseError;
 
     fn try_from(s: &String) -> Result<Self, Self::Error> {
-        let Some((host, port)) = s.rsplit_once(':') else {
-            return Err(HostPortPairError::NoPort);
-        };
+        Self::try_from(s.as_str())
+    }
+}
 
-        let port = port.parse()?;
+impl TryFrom<&str> for HostPortPair {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let (host, port) = split_host_port(s)?;
 
         Ok(HostPortPair {
-            host: host.into(),
+            host: host.to_owned(),
             port,
         })
     }
 }
 
-impl TryFrom<&str> for HostPortPair {
-    type Error = HostPortPairError;
+impl FromStr for Host {
+    type Err = ParseError;
 
-    fn try_from(s: &str) -> Result<Self, Self::Error> {
-        let Some((host, port)) = s.rsplit_once(':') else {
-            return Err(HostPortPairError::NoPort);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl FromStr for HostPortPair {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::try_from(s)
+    }
+}
+
+impl TryFrom<String> for HostMaybePort {
+    type Error = ParseError;
+
+    fn try_from(mut s: String) -> Result<Self, Self::Error> {
+        let (host, port) = match split_host_maybe_port(&s)? {
+            (HostRef::DnsName(name), port) => {
+                s.truncate(name.len());
+                (Host::DnsName(to_ascii_name(s)), port)
+            }
+            (host, port) => (host.to_owned(), port),
         };
 
-        let port = port.parse()?;
+        Ok(HostMaybePort { host, port })
+    }
+}
 
-        Ok(HostPortPair {
-            host: host.into(),
+impl TryFrom<&String> for HostMaybePort {
+    type Error = ParseError;
+
+    fn try_from(s: &String) -> Result<Self, Self::Error> {
+        Self::try_from(s.as_str())
+    }
+}
+
+impl TryFrom<&str> for HostMaybePort {
+    type Error = ParseError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        let (host, port) = split_host_maybe_port(s)?;
+
+        Ok(HostMaybePort {
+            host: host.to_owned(),
             port,
         })
     }
 }
 
-impl FromStr for HostPortPair {
-    type Err = HostPortPairError;
+impl FromStr for HostMaybePort {
+    type Err = ParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         Self::try_from(s)
@@ -221,22 +560,109 @@ impl From<HostPortPair> for (Host, u16) {
 
 impl Display for Host {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        match self {
-            Host::IpAddr(ip) => write!(f, "{ip}"),
-            Host::DnsName(name) => write!(f, "{name}"),
-        }
+        self.as_borrowed().fmt(f)
+    }
+}
+
+impl Display for ZoneId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.as_borrowed().fmt(f)
     }
 }
 
 impl Display for HostPortPair {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
-        write!(f, "{}:{}", self.host, self.port)
+        self.as_borrowed().fmt(f)
+    }
+}
+
+impl Display for HostMaybePort {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        self.host.as_borrowed().fmt_bracketed(f)?;
+
+        match self.port {
+            Some(port) => write!(f, ":{port}"),
+            None => Ok(()),
+        }
     }
 }
 
+// With the `idna` feature, non-ASCII names are converted to A-labels when possible so that the
+// Unicode and punycode spellings of a name compare equal. Invalid names are kept verbatim.
+#[cfg(feature = "idna")]
+fn to_ascii_name(name: String) -> String {
+    if name.is_ascii() {
+        return name;
+    }
+
+    idna_to_ascii(&name).unwrap_or(name)
+}
+
+#[cfg(feature = "idna")]
+fn idna_to_ascii(name: &str) -> Option<String> {
+    let (name, root) = match name.strip_suffix('.') {
+        Some(name) => (name, "."),
+        None => (name, ""),
+    };
+
+    idna::domain_to_ascii_strict(name)
+        .ok()
+        .map(|name| name + root)
+}
+
+#[cfg(not(feature = "idna"))]
+fn to_ascii_name(name: String) -> String {
+    name
+}
+
+#[cfg(feature = "idna")]
+fn has_a_label(name: &str) -> bool {
+    // Compares bytes, as a name that IDNA rejects is kept verbatim and may not be ASCII.
+    name.split('.').any(|label| {
+        label
+            .as_bytes()
+            .get(..4)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(b"xn--"))
+    })
+}
+
+fn trim_root(name: &str) -> &str {
+    name.strip_suffix('.').unwrap_or(name)
+}
+
+fn dns_name_eq(a: &str, b: &str) -> bool {
+    trim_root(a).eq_ignore_ascii_case(trim_root(b))
+}
+
+// Whether `name` is `domain` or a name under it.
+fn is_in_domain(name: &str, domain: &str) -> bool {
+    let (name, domain) = (trim_root(name), trim_root(domain));
+
+    match name.len().checked_sub(domain.len()) {
+        Some(0) => name.eq_ignore_ascii_case(domain),
+        Some(start) => {
+            name.as_bytes()[start - 1] == b'.' && name[start..].eq_ignore_ascii_case(domain)
+        }
+        None => false,
+    }
+}
+
+fn hash_dns_name<H: Hasher>(name: &str, state: &mut H) {
+    for chunk in trim_root(name).as_bytes().chunks(64) {
+        let mut buf = [0; 64];
+        let buf = &mut buf[..chunk.len()];
+        buf.copy_from_slice(chunk);
+        buf.make_ascii_lowercase();
+        state.write(buf);
+    }
+
+    state.write_u8(0xff);
+}
+
 #[cfg(feature = "serde")]
 mod serde {
     use super::*;
+    use crate::borrowed::reject_non_ascii_name;
     use ::serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
 
     impl Serialize for HostPortPair {
@@ -252,6 +678,19 @@ mod serde {
         }
     }
 
+    impl Serialize for HostMaybePort {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            ser.collect_str(self)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for HostMaybePort {
+        fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            let s = String::deserialize(de)?;
+            Self::try_from(s).map_err(DeError::custom)
+        }
+    }
+
     impl Serialize for Host {
         fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
             ser.collect_str(self)
@@ -264,11 +703,328 @@ mod serde {
             Ok(Self::from(s))
         }
     }
+
+    impl Serialize for HostPortPairRef<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            ser.collect_str(self)
+        }
+    }
+
+    impl<'de: 'a, 'a> Deserialize<'de> for HostPortPairRef<'a> {
+        fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            let s = <&str>::deserialize(de)?;
+            Self::try_from(s).map_err(DeError::custom)
+        }
+    }
+
+    impl Serialize for HostRef<'_> {
+        fn serialize<S: Serializer>(&self, ser: S) -> Result<S::Ok, S::Error> {
+            ser.collect_str(self)
+        }
+    }
+
+    // Rejects internationalized names, which `Host` would convert to A-labels.
+    impl<'de: 'a, 'a> Deserialize<'de> for HostRef<'a> {
+        fn deserialize<D: Deserializer<'de>>(de: D) -> Result<Self, D::Error> {
+            let s = <&str>::deserialize(de)?;
+            reject_non_ascii_name(Self::from(s)).map_err(DeError::custom)
+        }
+    }
 }
 
 #[cfg(feature = "rkyv")]
 pub mod rkyv {
+    use super::*;
+
     pub use crate::host_port_pair::{
-        ArchivedHost, ArchivedHostPortPair, HostPortPairResolver, HostResolver,
+        ArchivedHost, ArchivedHostMaybePort, ArchivedHostPortPair, ArchivedZoneId,
+        HostMaybePortResolver, HostPortPairResolver, HostResolver, ZoneIdResolver,
     };
+
+    impl ArchivedHost {
+        pub fn as_borrowed(&self) -> HostRef<'_> {
+            match self {
+                ArchivedHost::IpAddr(ip) => HostRef::IpAddr(ip.as_ipaddr()),
+                ArchivedHost::ScopedIpv6(ip, zone) => {
+                    HostRef::ScopedIpv6(ip.as_ipv6(), zone.as_borrowed())
+                }
+                ArchivedHost::DnsName(name) => HostRef::DnsName(name),
+            }
+        }
+    }
+
+    impl ArchivedZoneId {
+        pub fn as_borrowed(&self) -> ZoneIdRef<'_> {
+            match self {
+                ArchivedZoneId::Index(index) => ZoneIdRef::Index(index.to_native()),
+                ArchivedZoneId::Name(name) => ZoneIdRef::Name(name),
+            }
+        }
+    }
+
+    impl PartialEq<Host> for ArchivedHost {
+        fn eq(&self, other: &Host) -> bool {
+            self.as_borrowed() == other.as_borrowed()
+        }
+    }
+
+    impl Hash for ArchivedHost {
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.as_borrowed().hash(state)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn pair_round_trip() {
+        for s in [
+            "example.com:443",
+            "127.0.0.1:80",
+            "[::1]:8080",
+            "[2001:db8::1]:0",
+            "[fe80::1%25eth0]:22",
+            "[fe80::1%253]:65535",
+        ] {
+            let pair = HostPortPair::try_from(s).unwrap();
+            assert_eq!(pair.to_string(), s);
+            assert_eq!(s.parse::<HostPortPair>().unwrap(), pair);
+        }
+
+        let pair = HostPortPair::try_from("[::1]:8080").unwrap();
+        assert_eq!(pair.host(), &Host::IpAddr(IpAddr::V6(Ipv6Addr::LOCALHOST)));
+        assert_eq!(pair.port(), 8080);
+
+        for s in [
+            "example.com",
+            "example.com:",
+            "example.com:65536",
+            "::1:80",
+            "[::1",
+        ] {
+            assert!(HostPortPair::try_from(s).is_err(), "{s}");
+        }
+    }
+
+    #[test]
+    fn maybe_port_round_trip() {
+        for s in [
+            "example.com",
+            "example.com:443",
+            "127.0.0.1",
+            "[::1]",
+            "[::1]:8080",
+            "[fe80::1%25eth0]",
+        ] {
+            let host = HostMaybePort::try_from(s).unwrap();
+            assert_eq!(host.to_string(), s);
+            assert_eq!(s.parse::<HostMaybePort>().unwrap(), host);
+        }
+
+        let host = HostMaybePort::try_from("[::1]").unwrap();
+        assert_eq!(host.port(), None);
+        assert_eq!(host.with_default_port(443).to_string(), "[::1]:443");
+
+        let pair = HostPortPair::try_from("example.com:443").unwrap();
+        assert_eq!(
+            HostMaybePort::from(pair.clone()).to_string(),
+            "example.com:443"
+        );
+        assert_eq!(
+            HostPortPair::parse_with_default_port("example.com", 443).unwrap(),
+            pair
+        );
+    }
+
+    #[cfg(feature = "idna")]
+    #[test]
+    fn internationalized_names() {
+        let unicode = HostPortPair::try_from("bücher.example:80").unwrap();
+        let ascii = HostPortPair::try_from("xn--bcher-kva.example:80").unwrap();
+
+        assert_eq!(unicode, ascii);
+        assert_eq!(
+            unicode.host(),
+            &Host::DnsName("xn--bcher-kva.example".into())
+        );
+        assert_eq!(ascii.host().to_unicode(), "bücher.example");
+        assert_eq!(
+            Host::from("XN--BCHER-KVA.example.").to_unicode(),
+            "bücher.example."
+        );
+        assert_eq!(Host::from("example.com").to_unicode(), "example.com");
+        assert_eq!(Host::from("::1").to_unicode(), "::1");
+
+        // Names that IDNA rejects are kept verbatim.
+        let invalid = HostPortPair::try_from("aéé_x.com:80").unwrap();
+        assert_eq!(invalid.host(), &Host::DnsName("aéé_x.com".into()));
+        assert_eq!(invalid.host().to_unicode(), "aéé_x.com");
+        assert_eq!(Host::from("é.xn--bcher-kva").to_unicode(), "é.bücher");
+    }
+
+    #[test]
+    fn numeric_zone_round_trip() {
+        for scope_id in [1, 25, 250, 251, 2500, u32::MAX] {
+            let addr = SocketAddrV6::new("fe80::1".parse().unwrap(), 80, 0, scope_id);
+            let pair = HostPortPair::from(addr);
+            let parsed = pair.to_string().parse::<HostPortPair>().unwrap();
+
+            assert_eq!(parsed, pair);
+            assert_eq!(parsed.host().zone_id(), Some(&ZoneId::Index(scope_id)));
+        }
+    }
+
+    #[test]
+    fn zone_forms() {
+        let pair = HostPortPair::try_from("[fe80::1%eth0]:80").unwrap();
+        assert_eq!(pair.to_string(), "[fe80::1%25eth0]:80");
+        assert_eq!(HostPortPair::try_from("[fe80::1%25eth0]:80").unwrap(), pair);
+
+        // Without brackets, `25` is part of the zone.
+        let host = Host::from("fe80::1%250");
+        assert_eq!(host.zone_id(), Some(&ZoneId::Index(250)));
+        assert_eq!(Host::from(host.to_string().as_str()), host);
+    }
+
+    #[test]
+    fn parsing_reuses_string() {
+        let s = String::from("example.com:443");
+        let ptr = s.as_ptr();
+        let pair = HostPortPair::try_from(s).unwrap();
+
+        match pair.host() {
+            Host::DnsName(name) => assert_eq!(name.as_ptr(), ptr),
+            host => panic!("{host:?}"),
+        }
+    }
+
+    #[cfg(feature = "std")]
+    fn hash_of(value: &impl Hash) -> u64 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        value.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn equal_names_hash_equally() {
+        let cases = [
+            ("Example.COM:80", "example.com.:80", true),
+            ("example.com:80", "EXAMPLE.COM:80", true),
+            ("example.com.:80", "example.com:80", true),
+            ("example.com:80", "example.com:81", false),
+            ("example.com:80", "example.org:80", false),
+            ("example.com:80", "www.example.com:80", false),
+            ("[fe80::1%25eth0]:80", "[fe80::1%25eth0]:80", true),
+            ("[fe80::1%25eth0]:80", "[fe80::1%25eth1]:80", false),
+            ("[fe80::1%25eth0]:80", "[fe80::1]:80", false),
+        ];
+
+        for (a, b, equal) in cases {
+            let (a, b) = (
+                HostPortPair::try_from(a).unwrap(),
+                HostPortPair::try_from(b).unwrap(),
+            );
+
+            assert_eq!(a == b, equal, "{a} == {b}");
+            assert_eq!(b == a, equal, "{b} == {a}");
+            if equal {
+                assert_eq!(hash_of(&a), hash_of(&b), "{a} and {b}");
+                assert_eq!(hash_of(a.host()), hash_of(b.host()), "{a} and {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn socket_addr_equality() {
+        let cases = [
+            ("127.0.0.1:80", "127.0.0.1:80", true),
+            ("127.0.0.1:80", "127.0.0.1:81", false),
+            ("[::1]:80", "[::1]:80", true),
+            ("[::1]:80", "127.0.0.1:80", false),
+            ("[fe80::1%253]:80", "[fe80::1%3]:80", true),
+            ("[fe80::1%253]:80", "[fe80::1%4]:80", false),
+            ("[fe80::1%253]:80", "[fe80::1]:80", false),
+            ("[fe80::1]:80", "[fe80::1%3]:80", false),
+            ("localhost:80", "127.0.0.1:80", false),
+        ];
+
+        for (pair, addr, equal) in cases {
+            let pair = HostPortPair::try_from(pair).unwrap();
+            let addr = addr.parse::<SocketAddr>().unwrap();
+
+            assert_eq!(pair == addr, equal, "{pair} == {addr}");
+            assert_eq!(addr == pair, equal, "{addr} == {pair}");
+        }
+
+        // Named zones are never looked up, even for an interface that exists.
+        let pair = HostPortPair::try_from("[fe80::1%25lo]:80").unwrap();
+        let addr = SocketAddr::V6(SocketAddrV6::new("fe80::1".parse().unwrap(), 80, 0, 1));
+        assert_ne!(pair, addr);
+    }
+
+    #[test]
+    fn normalized() {
+        for (s, normalized) in [
+            ("Example.COM.:80", "example.com:80"),
+            ("example.com:80", "example.com:80"),
+            ("LOCALHOST.:8080", "localhost:8080"),
+            ("127.0.0.1:80", "127.0.0.1:80"),
+            ("[FE80::1%25eth0]:22", "[fe80::1%25eth0]:22"),
+        ] {
+            let pair = HostPortPair::try_from(s).unwrap();
+            let normalized_pair = pair.normalized();
+
+            assert_eq!(normalized_pair.to_string(), normalized);
+            assert_eq!(normalized_pair, pair);
+            assert_eq!(normalized_pair.normalized().to_string(), normalized);
+        }
+    }
+
+    #[cfg(feature = "rkyv")]
+    #[test]
+    fn archived_matches_native() {
+        use crate::rkyv::ArchivedHost;
+        use ::rkyv::rancor::Error;
+
+        for s in [
+            "Example.COM.",
+            "example.com",
+            "127.0.0.1",
+            "::1",
+            "fe80::1%eth0",
+            "fe80::1%3",
+        ] {
+            let host = Host::from(s);
+            let bytes = ::rkyv::to_bytes::<Error>(&host).unwrap();
+            let archived = ::rkyv::access::<ArchivedHost, Error>(&bytes).unwrap();
+
+            assert_eq!(archived, &host, "{s}");
+            let mut canonical = host.clone();
+            canonical.canonicalize();
+            assert_eq!(archived, &canonical, "{s}");
+            assert_eq!(archived.as_borrowed(), host.as_borrowed(), "{s}");
+            #[cfg(feature = "std")]
+            assert_eq!(hash_of(archived), hash_of(&host), "{s}");
+            assert_eq!(
+                ::rkyv::deserialize::<Host, Error>(archived).unwrap(),
+                host,
+                "{s}"
+            );
+        }
+
+        let bytes = ::rkyv::to_bytes::<Error>(&Host::from("example.com")).unwrap();
+        let archived = ::rkyv::access::<ArchivedHost, Error>(&bytes).unwrap();
+        assert_ne!(archived, &Host::from("example.org"));
+    }
+
+    #[test]
+    fn compact_layout() {
+        assert!(core::mem::size_of::<Host>() <= 32);
+        assert!(core::mem::size_of::<HostPortPair>() <= 40);
+    }
 }
@@ -0,0 +1,32 @@
+use crate::{HostPortPair, HostPortPairRef, HostRef, SocketAddrError};
+use std::{
+    io::{Error as IoError, ErrorKind, Result as IoResult},
+    net::{SocketAddr, ToSocketAddrs},
+    vec::IntoIter,
+};
+
+// IP hosts convert directly, so only DNS names reach the system resolver.
+impl ToSocketAddrs for HostPortPairRef<'_> {
+    type Iter = IntoIter<SocketAddr>;
+
+    fn to_socket_addrs(&self) -> IoResult<Self::Iter> {
+        match self.host {
+            HostRef::DnsName(name) => (name, self.port).to_socket_addrs(),
+            _ => match self.as_socket_addr() {
+                Some(addr) => Ok(vec![addr].into_iter()),
+                None => Err(IoError::new(
+                    ErrorKind::InvalidInput,
+                    SocketAddrError::UnknownZone,
+                )),
+            },
+        }
+    }
+}
+
+impl ToSocketAddrs for HostPortPair {
+    type Iter = IntoIter<SocketAddr>;
+
+    fn to_socket_addrs(&self) -> IoResult<Self::Iter> {
+        self.as_borrowed().to_socket_addrs()
+    }
+}
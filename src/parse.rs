@@ -0,0 +1,310 @@
+use crate::{
+    error::{DnsNameError, ParseError, ParseErrorKind},
+    trim_root, HostRef, ZoneIdRef,
+};
+use core::net::IpAddr;
+
+pub(crate) fn split_host_port(s: &str) -> Result<(HostRef<'_>, u16), ParseError> {
+    match split_host_maybe_port(s)? {
+        (host, Some(port)) => Ok((host, port)),
+        (HostRef::IpAddr(IpAddr::V6(_)) | HostRef::ScopedIpv6(..), None) if !s.starts_with('[') => {
+            Err(ParseError::new(ParseErrorKind::UnbracketedIpv6, 0..s.len()))
+        }
+        (_, None) => Err(ParseError::new(ParseErrorKind::NoPort, s.len()..s.len())),
+    }
+}
+
+pub(crate) fn split_host_maybe_port(s: &str) -> Result<(HostRef<'_>, Option<u16>), ParseError> {
+    if s.starts_with('[') {
+        let (ip, rest) = split_bracketed(s)?;
+        let ip = parse_ipv6_literal(ip, true).map_err(|err| err.offset(1))?;
+        let rest_start = s.len() - rest.len();
+
+        let port = match rest.strip_prefix(':') {
+            Some(port) => Some(parse_port(port).map_err(|err| err.offset(rest_start + 1))?),
+            None if rest.is_empty() => None,
+            None => {
+                return Err(ParseError::new(
+                    ParseErrorKind::TrailingGarbage,
+                    rest_start..s.len(),
+                ))
+            }
+        };
+
+        return Ok((ip, port));
+    }
+
+    match s.rsplit_once(':') {
+        None => Ok((parse_lenient_host(s)?, None)),
+        Some((host, port)) if !host.contains(':') => {
+            let host = parse_lenient_host(host)?;
+            let port = parse_port(port).map_err(|err| err.offset(s.len() - port.len()))?;
+            Ok((host, Some(port)))
+        }
+        Some(_) => match parse_ipv6_literal(s, false) {
+            Ok(ip) => Ok((ip, None)),
+            Err(_) => Err(ParseError::new(ParseErrorKind::UnbracketedIpv6, 0..s.len())),
+        },
+    }
+}
+
+// Splits `[inner]rest`, where `s` starts with `[`.
+fn split_bracketed(s: &str) -> Result<(&str, &str), ParseError> {
+    match s.find(']') {
+        Some(close) => Ok((&s[1..close], &s[close + 1..])),
+        None => Err(ParseError::new(ParseErrorKind::UnclosedBracket, 0..s.len())),
+    }
+}
+
+// Accepts any host that is not empty and has no whitespace, control characters or URL delimiters.
+fn parse_lenient_host(host: &str) -> Result<HostRef<'_>, ParseError> {
+    if host.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::EmptyHost, 0..0));
+    }
+
+    if let Some(ip) = parse_ip_host(host) {
+        return Ok(ip);
+    }
+
+    match host.char_indices().find(|(_, c)| is_invalid_host_char(*c)) {
+        Some((i, c)) => Err(ParseError::new(
+            ParseErrorKind::InvalidHostChar(c),
+            i..i + c.len_utf8(),
+        )),
+        None => Ok(HostRef::DnsName(host)),
+    }
+}
+
+// Characters that can't appear in a DNS name, even leniently, as they delimit other parts of a
+// URL or an address.
+pub(crate) fn is_invalid_host_char(c: char) -> bool {
+    c.is_whitespace() || c.is_control() || matches!(c, '/' | '?' | '#' | '@' | '[' | ']' | '\\')
+}
+
+pub(crate) fn parse_strict_host(s: &str) -> Result<HostRef<'_>, ParseError> {
+    if s.starts_with('[') {
+        let (ip, rest) = split_bracketed(s)?;
+
+        if !rest.is_empty() {
+            let start = s.len() - rest.len();
+            return Err(ParseError::new(
+                ParseErrorKind::TrailingGarbage,
+                start..s.len(),
+            ));
+        }
+
+        return parse_ipv6_literal(ip, true).map_err(|err| err.offset(1));
+    }
+
+    if let Some(host) = parse_ip_host(s) {
+        return Ok(host);
+    }
+
+    validate_dns_name(s)?;
+    Ok(HostRef::DnsName(s))
+}
+
+fn parse_port(s: &str) -> Result<u16, ParseError> {
+    if s.is_empty() {
+        return Err(ParseError::new(ParseErrorKind::EmptyPort, 0..0));
+    }
+
+    if s.starts_with(['+', '-']) {
+        return Err(ParseError::new(ParseErrorKind::SignedPort, 0..1));
+    }
+
+    if let Some((i, c)) = s.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidPortChar(c),
+            i..i + c.len_utf8(),
+        ));
+    }
+
+    s.parse()
+        .map_err(|_| ParseError::new(ParseErrorKind::PortOutOfRange, 0..s.len()))
+}
+
+pub(crate) fn parse_ip_host(host: &str) -> Option<HostRef<'_>> {
+    match host.strip_prefix('[').and_then(|h| h.strip_suffix(']')) {
+        Some(ip) => parse_ipv6_literal(ip, true).ok(),
+        None => match host.parse() {
+            Ok(ip) => Some(HostRef::IpAddr(ip)),
+            Err(_) => parse_ipv6_literal(host, false).ok(),
+        },
+    }
+}
+
+// Inside brackets, a zone may be in the RFC 6874 URI form `%25zone`, which is how zones are
+// displayed there, or in the plain `%zone` form. Without brackets, only the plain form is used.
+fn parse_ipv6_literal(s: &str, bracketed: bool) -> Result<HostRef<'_>, ParseError> {
+    let (ip, zone) = match s.split_once('%') {
+        Some((ip, zone)) => (ip, Some(zone)),
+        None => (s, None),
+    };
+
+    let Ok(ip) = ip.parse() else {
+        return Err(ParseError::new(
+            ParseErrorKind::InvalidIpv6Literal,
+            0..ip.len(),
+        ));
+    };
+
+    let Some(zone) = zone else {
+        return Ok(HostRef::IpAddr(IpAddr::V6(ip)));
+    };
+
+    let zone = match zone.strip_prefix("25") {
+        Some(zone) if bracketed && !zone.is_empty() => zone,
+        _ => zone,
+    };
+
+    match parse_zone_id(zone) {
+        Some(zone) => Ok(HostRef::ScopedIpv6(ip, zone)),
+        None => Err(ParseError::new(
+            ParseErrorKind::InvalidZoneId,
+            s.len() - zone.len()..s.len(),
+        )),
+    }
+}
+
+fn parse_zone_id(s: &str) -> Option<ZoneIdRef<'_>> {
+    let is_valid = |b: u8| b.is_ascii_alphanumeric() || b"-._~".contains(&b);
+
+    if s.is_empty() || !s.bytes().all(is_valid) {
+        return None;
+    }
+
+    if s.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok().map(ZoneIdRef::Index)
+    } else {
+        Some(ZoneIdRef::Name(s))
+    }
+}
+
+pub(crate) fn validate_dns_name(name: &str) -> Result<(), ParseError> {
+    let error = |err, span| ParseError::new(ParseErrorKind::InvalidDnsName(err), span);
+    let trimmed = trim_root(name);
+
+    if trimmed.is_empty() {
+        return Err(error(DnsNameError::Empty, 0..name.len()));
+    }
+
+    if trimmed.len() > 253 {
+        return Err(error(DnsNameError::TooLong, 0..name.len()));
+    }
+
+    let mut start = 0;
+
+    for label in trimmed.split('.') {
+        let end = start + label.len();
+
+        if label.is_empty() {
+            return Err(error(DnsNameError::EmptyLabel, start..start));
+        }
+
+        if label.len() > 63 {
+            return Err(error(DnsNameError::LabelTooLong, start..end));
+        }
+
+        if let Some((i, c)) = label
+            .char_indices()
+            .find(|(_, c)| !c.is_ascii_alphanumeric() && *c != '-')
+        {
+            let i = start + i;
+            return Err(error(DnsNameError::InvalidChar(c), i..i + c.len_utf8()));
+        }
+
+        if label.starts_with('-') {
+            return Err(error(DnsNameError::LeadingHyphen, start..start + 1));
+        }
+
+        if label.ends_with('-') {
+            return Err(error(DnsNameError::TrailingHyphen, end - 1..end));
+        }
+
+        start = end + 1;
+    }
+
+    // RFC 1123 section 2.1: a name can't look like a dotted-decimal address. Resolvers accept
+    // forms such as `127.1` and `1.2.3` as addresses, so these names are rejected outright.
+    let tld = trimmed.rsplit('.').next().unwrap_or(trimmed);
+
+    if tld.bytes().all(|b| b.is_ascii_digit()) {
+        let tld_start = trimmed.len() - tld.len();
+        return Err(error(
+            DnsNameError::NumericTopLevel,
+            tld_start..trimmed.len(),
+        ));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DnsNameError, Host, ParseErrorKind};
+    use alloc::format;
+    use core::ops::Range;
+
+    fn dns_name_error(s: &str) -> (DnsNameError, Range<usize>) {
+        let err = Host::parse(s).unwrap_err();
+
+        match err.kind() {
+            ParseErrorKind::InvalidDnsName(kind) => (*kind, err.span()),
+            kind => panic!("{s:?}: {kind:?}"),
+        }
+    }
+
+    #[test]
+    fn valid_names() {
+        let max_label = "a".repeat(63);
+        let max_name = format!("{0}.{0}.{0}.{1}", max_label, "a".repeat(61));
+        assert_eq!(max_name.len(), 253);
+
+        for s in [
+            "a",
+            "localhost",
+            "example.com",
+            "example.com.",
+            "EXAMPLE.com",
+            "a-b.c-d.example",
+            "1password.com",
+            "123.example",
+            "example.123a",
+            &max_label,
+            &max_name,
+            &format!("{max_name}."),
+        ] {
+            assert!(Host::parse(s).unwrap().is_dns_name(), "{s:?}");
+        }
+
+        assert!(Host::parse("127.0.0.1").unwrap().is_ip_address());
+        assert!(Host::parse("[::1]").unwrap().is_ip_address());
+    }
+
+    #[test]
+    fn invalid_names() {
+        let long_label = "a".repeat(64);
+        let long_name = format!("{0}.{0}.{0}.{1}", "a".repeat(63), "a".repeat(62));
+
+        let cases: [(&str, DnsNameError, Range<usize>); 13] = [
+            ("", DnsNameError::Empty, 0..0),
+            (".", DnsNameError::Empty, 0..1),
+            (&long_name, DnsNameError::TooLong, 0..254),
+            ("a..b", DnsNameError::EmptyLabel, 2..2),
+            (".a", DnsNameError::EmptyLabel, 0..0),
+            (&long_label, DnsNameError::LabelTooLong, 0..64),
+            ("a_b.example", DnsNameError::InvalidChar('_'), 1..2),
+            ("a b", DnsNameError::InvalidChar(' '), 1..2),
+            ("-a.example", DnsNameError::LeadingHyphen, 0..1),
+            ("a.b-.example", DnsNameError::TrailingHyphen, 3..4),
+            ("256.1.1.1", DnsNameError::NumericTopLevel, 8..9),
+            ("1.2.3", DnsNameError::NumericTopLevel, 4..5),
+            ("127.1.", DnsNameError::NumericTopLevel, 4..5),
+        ];
+
+        for (s, kind, span) in cases {
+            assert_eq!(dns_name_error(s), (kind, span), "{s:?}");
+        }
+    }
+}
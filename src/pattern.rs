@@ -0,0 +1,120 @@
+use crate::{
+    dns_name_eq, is_in_domain, parse::parse_ip_host, Host, HostRef, IpNet, ParseError,
+    ParseErrorKind,
+};
+use alloc::string::String;
+use core::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    net::IpAddr,
+    str::FromStr,
+};
+
+/// A pattern that matches hosts, see [`Host::matches`].
+///
+/// DNS names are compared case-insensitively and without their trailing root dot.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HostPattern {
+    /// Addresses in a network, written `10.0.0.0/8`.
+    Net(IpNet),
+    /// A single name or address, written `example.com` or `::1`.
+    Host(Host),
+    /// Names under a domain but not the domain itself, written `*.example.com`.
+    Wildcard(String),
+    /// A domain and the names under it, written `.example.com`.
+    DomainSuffix(String),
+}
+
+impl HostRef<'_> {
+    /// Returns whether the host matches `pattern`. IPv4-mapped IPv6 addresses also match networks
+    /// that contain the IPv4 addresses they carry.
+    pub fn matches(&self, pattern: &HostPattern) -> bool {
+        match (pattern, *self) {
+            (HostPattern::Net(net), host) => host
+                .ip_addr()
+                .is_some_and(|ip| net.contains(ip) || net.contains(ip.to_canonical())),
+            (HostPattern::Host(expected), host) => expected.as_borrowed() == host,
+            (HostPattern::Wildcard(domain), HostRef::DnsName(name)) => {
+                is_in_domain(name, domain) && !dns_name_eq(name, domain)
+            }
+            (HostPattern::DomainSuffix(domain), HostRef::DnsName(name)) => {
+                is_in_domain(name, domain)
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Host {
+    /// Returns whether the host matches `pattern`. IPv4-mapped IPv6 addresses also match networks
+    /// that contain the IPv4 addresses they carry.
+    pub fn matches(&self, pattern: &HostPattern) -> bool {
+        self.as_borrowed().matches(pattern)
+    }
+}
+
+impl Display for HostPattern {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            HostPattern::Net(net) => net.fmt(f),
+            HostPattern::Host(host) => host.fmt(f),
+            HostPattern::Wildcard(domain) => write!(f, "*.{domain}"),
+            HostPattern::DomainSuffix(domain) => write!(f, ".{domain}"),
+        }
+    }
+}
+
+impl FromStr for HostPattern {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.contains('/') {
+            return s.parse().map(HostPattern::Net);
+        }
+
+        if let Some(domain) = s.strip_prefix("*.") {
+            return parse_domain(domain)
+                .map_err(|err| err.offset(2))
+                .map(HostPattern::Wildcard);
+        }
+
+        if let Some(domain) = s.strip_prefix('.') {
+            return parse_domain(domain)
+                .map_err(|err| err.offset(1))
+                .map(HostPattern::DomainSuffix);
+        }
+
+        // Unlike in host-port pairs, IPv6 addresses don't need brackets here.
+        match parse_ip_host(s) {
+            Some(ip) => Ok(HostPattern::Host(ip.to_owned())),
+            None => Host::parse(s).map(HostPattern::Host),
+        }
+    }
+}
+
+impl From<IpNet> for HostPattern {
+    fn from(net: IpNet) -> Self {
+        HostPattern::Net(net)
+    }
+}
+
+impl From<Host> for HostPattern {
+    fn from(host: Host) -> Self {
+        HostPattern::Host(host)
+    }
+}
+
+impl From<IpAddr> for HostPattern {
+    fn from(ip: IpAddr) -> Self {
+        HostPattern::Host(Host::IpAddr(ip))
+    }
+}
+
+fn parse_domain(s: &str) -> Result<String, ParseError> {
+    match Host::parse(s)? {
+        Host::DnsName(name) => Ok(name),
+        _ => Err(ParseError::new(
+            ParseErrorKind::InvalidDomainSuffix,
+            0..s.len(),
+        )),
+    }
+}
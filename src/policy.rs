@@ -0,0 +1,280 @@
+use crate::{Host, HostPattern, HostPortPair};
+use alloc::vec::Vec;
+use core::{
+    net::{IpAddr, SocketAddr},
+    ops::RangeInclusive,
+};
+
+/// Decides which destinations may be connected to, such as user-supplied webhook targets.
+///
+/// Rules are checked in order and the first matching rule decides. If no rule matches, the
+/// default action applies. IPv4-mapped IPv6 addresses are matched as the IPv4 addresses they
+/// carry.
+///
+/// A DNS name can resolve to any address, so a name that is allowed before resolution must be
+/// checked again with [`evaluate_resolved`](Self::evaluate_resolved), and the connection must use
+/// the checked addresses rather than resolving the name again.
+#[derive(Debug, Clone)]
+pub struct DestinationPolicy {
+    rules: Vec<Rule>,
+    default: Action,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Allow,
+    Deny,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    action: Action,
+    host: HostMatcher,
+    ports: RangeInclusive<u16>,
+}
+
+/// The hosts that a [`Rule`] applies to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostMatcher {
+    Any,
+    /// Addresses that are not globally reachable, see [`Host::is_global`]. DNS names only match
+    /// through their resolved addresses.
+    NonGlobal,
+    /// Hosts that match a pattern, see [`Host::matches`]. Name patterns match the destination
+    /// as given. Address and network patterns match its address, so DNS names only match through
+    /// their resolved addresses. Zones are ignored.
+    Pattern(HostPattern),
+}
+
+/// The outcome of evaluating a [`DestinationPolicy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Decision<'a> {
+    action: Action,
+    rule: Option<(usize, &'a Rule)>,
+    addr: Option<SocketAddr>,
+}
+
+impl DestinationPolicy {
+    pub fn new(default: Action) -> Self {
+        DestinationPolicy {
+            rules: Vec::new(),
+            default,
+        }
+    }
+
+    /// Adds a rule after the existing ones.
+    pub fn with_rule(mut self, rule: Rule) -> Self {
+        self.rules.push(rule);
+        self
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn default_action(&self) -> Action {
+        self.default
+    }
+
+    /// Evaluates a destination before resolution. Rules on addresses don't match DNS names.
+    pub fn evaluate(&self, pair: &HostPortPair) -> Decision<'_> {
+        let ip = pair.host().ip_addr();
+        self.decide(Some(pair.host()), ip, pair.port(), None)
+    }
+
+    /// Evaluates a resolved address on its own. Rules on DNS names don't match.
+    pub fn evaluate_addr(&self, addr: &SocketAddr) -> Decision<'_> {
+        self.decide(None, Some(addr.ip()), addr.port(), Some(*addr))
+    }
+
+    /// Evaluates a destination together with the addresses it resolved to.
+    ///
+    /// Each address is evaluated with rules on names matching `pair` and rules on addresses
+    /// matching the address, so a name can be allowed to resolve to addresses that are otherwise
+    /// denied. Returns the decision for the first denied address, or else for the last address.
+    /// Without addresses, this is the same as [`evaluate`](Self::evaluate).
+    pub fn evaluate_resolved(&self, pair: &HostPortPair, addrs: &[SocketAddr]) -> Decision<'_> {
+        let mut decision = self.evaluate(pair);
+
+        for addr in addrs {
+            decision = self.decide(Some(pair.host()), Some(addr.ip()), addr.port(), Some(*addr));
+
+            if decision.action == Action::Deny {
+                break;
+            }
+        }
+
+        decision
+    }
+
+    fn decide(
+        &self,
+        host: Option<&Host>,
+        ip: Option<IpAddr>,
+        port: u16,
+        addr: Option<SocketAddr>,
+    ) -> Decision<'_> {
+        let ip = ip.map(|ip| ip.to_canonical());
+
+        let rule = self
+            .rules
+            .iter()
+            .enumerate()
+            .find(|(_, rule)| rule.ports.contains(&port) && rule.host.matches(host, ip));
+
+        Decision {
+            action: rule.map_or(self.default, |(_, rule)| rule.action),
+            rule,
+            addr,
+        }
+    }
+}
+
+impl Rule {
+    /// Applies to all ports unless narrowed with [`with_ports`](Self::with_ports).
+    pub fn new(action: Action, host: HostMatcher) -> Self {
+        Rule {
+            action,
+            host,
+            ports: 0..=u16::MAX,
+        }
+    }
+
+    pub fn allow(host: HostMatcher) -> Self {
+        Self::new(Action::Allow, host)
+    }
+
+    pub fn deny(host: HostMatcher) -> Self {
+        Self::new(Action::Deny, host)
+    }
+
+    pub fn with_ports(mut self, ports: RangeInclusive<u16>) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    pub fn host(&self) -> &HostMatcher {
+        &self.host
+    }
+
+    pub fn ports(&self) -> &RangeInclusive<u16> {
+        &self.ports
+    }
+}
+
+impl HostMatcher {
+    // `host` is the destination as given, and `ip` its canonical address, if known.
+    fn matches(&self, host: Option<&Host>, ip: Option<IpAddr>) -> bool {
+        let pattern = match self {
+            HostMatcher::Any => return true,
+            HostMatcher::NonGlobal => return ip.is_some_and(|ip| !Host::IpAddr(ip).is_global()),
+            HostMatcher::Pattern(pattern) => pattern,
+        };
+
+        match pattern {
+            HostPattern::Net(_) => ip.is_some_and(|ip| Host::IpAddr(ip).matches(pattern)),
+            HostPattern::Host(expected) => match expected.ip_addr() {
+                Some(expected) => ip == Some(expected.to_canonical()),
+                None => host.is_some_and(|host| host.matches(pattern)),
+            },
+            HostPattern::Wildcard(_) | HostPattern::DomainSuffix(_) => {
+                host.is_some_and(|host| host.matches(pattern))
+            }
+        }
+    }
+}
+
+impl From<HostPattern> for HostMatcher {
+    fn from(pattern: HostPattern) -> Self {
+        HostMatcher::Pattern(pattern)
+    }
+}
+
+impl<'a> Decision<'a> {
+    pub fn action(&self) -> Action {
+        self.action
+    }
+
+    pub fn is_allowed(&self) -> bool {
+        self.action == Action::Allow
+    }
+
+    /// Returns the index and the rule that decided, or `None` if the default action applied.
+    pub fn rule(&self) -> Option<(usize, &'a Rule)> {
+        self.rule
+    }
+
+    /// Returns the resolved address that was evaluated last, if any.
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(s: &str) -> HostMatcher {
+        HostMatcher::from(s.parse::<HostPattern>().unwrap())
+    }
+
+    fn pair(s: &str) -> HostPortPair {
+        HostPortPair::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn patterns() {
+        let policy = DestinationPolicy::new(Action::Allow)
+            .with_rule(Rule::allow(pattern("api.internal.example")))
+            .with_rule(Rule::deny(pattern(".internal.example")))
+            .with_rule(Rule::deny(pattern("*.corp.example")))
+            .with_rule(Rule::deny(pattern("10.0.0.0/8")))
+            .with_rule(Rule::deny(pattern("192.0.2.1")))
+            .with_rule(Rule::deny(HostMatcher::NonGlobal).with_ports(0..=1023));
+
+        assert!(policy
+            .evaluate(&pair("API.Internal.Example.:443"))
+            .is_allowed());
+        assert!(!policy.evaluate(&pair("internal.example:443")).is_allowed());
+        assert!(!policy.evaluate(&pair("www.corp.example:443")).is_allowed());
+        assert!(policy.evaluate(&pair("corp.example:443")).is_allowed());
+
+        assert!(!policy.evaluate(&pair("10.1.2.3:443")).is_allowed());
+        assert!(!policy.evaluate(&pair("[::ffff:10.1.2.3]:443")).is_allowed());
+        assert!(!policy
+            .evaluate(&pair("[::ffff:192.0.2.1]:443"))
+            .is_allowed());
+        assert!(!policy.evaluate(&pair("127.0.0.1:80")).is_allowed());
+        assert!(policy.evaluate(&pair("127.0.0.1:8080")).is_allowed());
+
+        let decision = policy.evaluate(&pair("10.1.2.3:443"));
+        assert_eq!(decision.rule().map(|(index, _)| index), Some(3));
+    }
+
+    #[test]
+    fn resolved_addresses() {
+        let policy = DestinationPolicy::new(Action::Allow)
+            .with_rule(Rule::allow(pattern(".trusted.example")))
+            .with_rule(Rule::deny(HostMatcher::NonGlobal));
+
+        let addrs = [
+            "8.8.8.8:443".parse().unwrap(),
+            "10.0.0.1:443".parse().unwrap(),
+        ];
+
+        let decision = policy.evaluate_resolved(&pair("rebind.example:443"), &addrs);
+        assert!(!decision.is_allowed());
+        assert_eq!(decision.addr(), Some(addrs[1]));
+
+        let decision = policy.evaluate_resolved(&pair("db.trusted.example:443"), &addrs);
+        assert!(decision.is_allowed());
+
+        // Names never match address rules before resolution.
+        assert!(policy.evaluate(&pair("localhost:443")).is_allowed());
+        assert!(!policy.evaluate_addr(&addrs[1]).is_allowed());
+    }
+}
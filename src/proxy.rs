@@ -0,0 +1,359 @@
+use crate::{
+    parse::split_host_maybe_port,
+    url::{percent_decode, split_authority},
+    Host, HostPattern, HostPortPair, HostRef, ParseError, ParseErrorKind,
+};
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::str::FromStr;
+
+/// Proxy settings from the `http_proxy`, `https_proxy`, `all_proxy` and `no_proxy` environment
+/// variables, following curl.
+///
+/// Each variable is read in lowercase first and then in uppercase, except `http_proxy`, which is
+/// only read in lowercase. CGI servers set `HTTP_PROXY` from the `Proxy` request header, so a
+/// client could otherwise pick the proxy (the "httpoxy" vulnerability). Empty values count as
+/// unset, and proxy values that fail to parse are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProxyEnv {
+    http: Option<ProxyTarget>,
+    https: Option<ProxyTarget>,
+    all: Option<ProxyTarget>,
+    no_proxy: NoProxy,
+}
+
+/// A proxy server, parsed from a value such as `http://user:pw@proxy.example.com:3128`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyTarget {
+    scheme: ProxyScheme,
+    addr: HostPortPair,
+    username: Option<String>,
+    password: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProxyScheme {
+    Http,
+    Https,
+    Socks4,
+    Socks4a,
+    Socks5,
+    Socks5h,
+}
+
+/// The hosts that bypass the proxy, parsed from a `no_proxy` value.
+///
+/// Entries are separated by commas or whitespace. A name matches itself and the names under it,
+/// with or without a leading dot. Addresses match exactly, and networks such as `10.0.0.0/8`
+/// match the addresses in them. IPv6 addresses may be bracketed, and an entry with a port, such as
+/// `example.com:8080`, only matches that port. Names never match addresses, as nothing is
+/// resolved. A value of `*` matches every host. Invalid entries are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NoProxy {
+    all: bool,
+    entries: Vec<NoProxyEntry>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct NoProxyEntry {
+    pattern: HostPattern,
+    port: Option<u16>,
+}
+
+impl ProxyEnv {
+    /// Reads the variables from the process environment.
+    #[cfg(feature = "std")]
+    pub fn from_env() -> Self {
+        Self::from_lookup(|name| std::env::var(name).ok())
+    }
+
+    /// Reads the variables with `lookup`, which returns the value of a variable by name.
+    pub fn from_lookup(mut lookup: impl FnMut(&str) -> Option<String>) -> Self {
+        let mut var = |names: &[&str]| {
+            names
+                .iter()
+                .find_map(|name| lookup(name).filter(|value| !value.is_empty()))
+        };
+
+        let mut proxy = |names: &[&str]| var(names).and_then(|value| value.parse().ok());
+
+        ProxyEnv {
+            http: proxy(&["http_proxy"]),
+            https: proxy(&["https_proxy", "HTTPS_PROXY"]),
+            all: proxy(&["all_proxy", "ALL_PROXY"]),
+            no_proxy: var(&["no_proxy", "NO_PROXY"])
+                .map(|value| NoProxy::parse(&value))
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Returns the proxy for connecting to `pair` with the URL scheme `scheme`, or `None` to
+    /// connect directly.
+    ///
+    /// `http` and `https` use their own variable and fall back to `all_proxy`. Other schemes only
+    /// use `all_proxy`.
+    pub fn proxy_for(&self, pair: &HostPortPair, scheme: &str) -> Option<ProxyTarget> {
+        if self.no_proxy.matches(pair) {
+            return None;
+        }
+
+        let proxy = if scheme.eq_ignore_ascii_case("http") {
+            self.http.as_ref()
+        } else if scheme.eq_ignore_ascii_case("https") {
+            self.https.as_ref()
+        } else {
+            None
+        };
+
+        proxy.or(self.all.as_ref()).cloned()
+    }
+
+    pub fn http(&self) -> Option<&ProxyTarget> {
+        self.http.as_ref()
+    }
+
+    pub fn https(&self) -> Option<&ProxyTarget> {
+        self.https.as_ref()
+    }
+
+    pub fn all(&self) -> Option<&ProxyTarget> {
+        self.all.as_ref()
+    }
+
+    pub fn no_proxy(&self) -> &NoProxy {
+        &self.no_proxy
+    }
+}
+
+impl ProxyTarget {
+    /// Parses `[scheme://][user[:password]@]host[:port]`, where the scheme defaults to `http`.
+    ///
+    /// Like curl, the port defaults to 443 for `https` and to 1080 otherwise. Anything after the
+    /// authority is ignored.
+    pub fn parse(s: &str) -> Result<Self, ParseError> {
+        let (scheme, rest) = match s.split_once("://") {
+            Some((scheme, rest)) => match ProxyScheme::from_name(scheme) {
+                Some(scheme) => (scheme, rest),
+                None => {
+                    return Err(ParseError::new(
+                        ParseErrorKind::UnsupportedProxyScheme(scheme.to_owned()),
+                        0..scheme.len(),
+                    ))
+                }
+            },
+            None => (ProxyScheme::Http, s),
+        };
+
+        let start = s.len() - rest.len();
+
+        let authority = match rest.find(['/', '?', '#']) {
+            Some(end) => &rest[..end],
+            None => rest,
+        };
+
+        let (host, port) = split_authority(authority).map_err(|err| err.offset(start))?;
+
+        let (username, password) = match authority.rsplit_once('@') {
+            Some((userinfo, _)) => {
+                let (username, password) = match userinfo.split_once(':') {
+                    Some((username, password)) => (username, Some(password)),
+                    None => (userinfo, None),
+                };
+
+                let decode = |s: &str, offset: usize| {
+                    percent_decode(s)
+                        .map(|s| s.into_owned())
+                        .map_err(|err| err.offset(start + offset))
+                };
+
+                let password = match password {
+                    Some(password) => Some(decode(password, username.len() + 1)?),
+                    None => None,
+                };
+
+                (Some(decode(username, 0)?), password)
+            }
+            None => (None, None),
+        };
+
+        Ok(ProxyTarget {
+            scheme,
+            addr: HostPortPair {
+                host,
+                port: port.unwrap_or(scheme.default_port()),
+            },
+            username,
+            password,
+        })
+    }
+
+    pub fn scheme(&self) -> ProxyScheme {
+        self.scheme
+    }
+
+    pub fn addr(&self) -> &HostPortPair {
+        &self.addr
+    }
+
+    pub fn username(&self) -> Option<&str> {
+        self.username.as_deref()
+    }
+
+    pub fn password(&self) -> Option<&str> {
+        self.password.as_deref()
+    }
+}
+
+impl FromStr for ProxyTarget {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl ProxyScheme {
+    /// Returns the scheme for a URL scheme name, compared case-insensitively.
+    pub fn from_name(name: &str) -> Option<Self> {
+        [
+            ("http", ProxyScheme::Http),
+            ("https", ProxyScheme::Https),
+            ("socks4", ProxyScheme::Socks4),
+            ("socks4a", ProxyScheme::Socks4a),
+            ("socks5", ProxyScheme::Socks5),
+            ("socks5h", ProxyScheme::Socks5h),
+        ]
+        .into_iter()
+        .find(|(scheme, _)| scheme.eq_ignore_ascii_case(name))
+        .map(|(_, scheme)| scheme)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProxyScheme::Http => "http",
+            ProxyScheme::Https => "https",
+            ProxyScheme::Socks4 => "socks4",
+            ProxyScheme::Socks4a => "socks4a",
+            ProxyScheme::Socks5 => "socks5",
+            ProxyScheme::Socks5h => "socks5h",
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            ProxyScheme::Https => 443,
+            _ => 1080,
+        }
+    }
+}
+
+impl NoProxy {
+    pub fn parse(value: &str) -> Self {
+        if value.trim() == "*" {
+            return NoProxy {
+                all: true,
+                entries: Vec::new(),
+            };
+        }
+
+        let entries = value
+            .split(|c: char| c == ',' || c.is_ascii_whitespace())
+            .filter_map(NoProxyEntry::parse)
+            .collect();
+
+        NoProxy {
+            all: false,
+            entries,
+        }
+    }
+
+    /// Returns whether connections to `pair` bypass the proxy.
+    pub fn matches(&self, pair: &HostPortPair) -> bool {
+        self.all
+            || self.entries.iter().any(|entry| {
+                entry.port.is_none_or(|port| port == pair.port())
+                    && pair.host().matches(&entry.pattern)
+            })
+    }
+}
+
+impl NoProxyEntry {
+    fn parse(entry: &str) -> Option<Self> {
+        let entry = entry.strip_prefix('.').unwrap_or(entry);
+
+        if entry.is_empty() {
+            return None;
+        }
+
+        if entry.contains('/') {
+            let pattern = HostPattern::Net(entry.parse().ok()?);
+            return Some(NoProxyEntry {
+                pattern,
+                port: None,
+            });
+        }
+
+        let (host, port) = split_host_maybe_port(entry).ok()?;
+
+        let pattern = match host {
+            HostRef::DnsName(name) => match Host::from(name) {
+                Host::DnsName(name) => HostPattern::DomainSuffix(name),
+                host => HostPattern::Host(host),
+            },
+            ip => HostPattern::Host(ip.to_owned()),
+        };
+
+        Some(NoProxyEntry { pattern, port })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn env(vars: &[(&str, &str)]) -> ProxyEnv {
+        ProxyEnv::from_lookup(|name| {
+            vars.iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| value.to_string())
+        })
+    }
+
+    #[test]
+    fn uppercase_http_proxy_is_ignored() {
+        let env = env(&[
+            ("HTTP_PROXY", "http://attacker.example:8080"),
+            ("HTTPS_PROXY", "http://proxy.example:3128"),
+            ("NO_PROXY", "internal.example"),
+        ]);
+
+        assert_eq!(env.http(), None);
+        assert_eq!(
+            env.https().unwrap().addr().to_string(),
+            "proxy.example:3128"
+        );
+
+        let pair = HostPortPair::try_from("example.com:80").unwrap();
+        assert_eq!(env.proxy_for(&pair, "http"), None);
+
+        let pair = HostPortPair::try_from("www.internal.example:443").unwrap();
+        assert_eq!(env.proxy_for(&pair, "https"), None);
+    }
+
+    #[test]
+    fn lowercase_first() {
+        let env = env(&[
+            ("http_proxy", "proxy.example"),
+            ("all_proxy", ""),
+            ("ALL_PROXY", "socks5h://socks.example"),
+        ]);
+
+        let http = env.http().unwrap();
+        assert_eq!(http.scheme(), ProxyScheme::Http);
+        assert_eq!(http.addr().to_string(), "proxy.example:1080");
+
+        let pair = HostPortPair::try_from("example.com:22").unwrap();
+        let proxy = env.proxy_for(&pair, "ssh").unwrap();
+        assert_eq!(proxy.scheme(), ProxyScheme::Socks5h);
+    }
+}
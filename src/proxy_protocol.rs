@@ -0,0 +1,662 @@
+use crate::{Host, HostPortPair};
+use alloc::{string::String, vec::Vec};
+use core::{
+    fmt::Write,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+use thiserror::Error;
+
+const V1_PREFIX: &[u8] = b"PROXY ";
+const V2_SIGNATURE: &[u8] = b"\r\n\r\n\0\r\nQUIT\n";
+
+/// The longest version 1 header, including the CRLF.
+pub const MAX_V1_LEN: usize = 107;
+
+/// The longest version 2 header.
+pub const MAX_V2_LEN: usize = 16 + u16::MAX as usize;
+
+const V2_HEADER_LEN: usize = 16;
+const V2_INET_LEN: usize = 12;
+const V2_INET6_LEN: usize = 36;
+const V2_UNIX_LEN: usize = 216;
+
+/// A header of the PROXY protocol, which load balancers send before the client's data to pass on
+/// the original source and destination of a connection.
+///
+/// See <https://www.haproxy.org/download/2.9/doc/proxy-protocol.txt>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProxyHeader {
+    command: Command,
+    protocol: Protocol,
+    addrs: Option<(HostPortPair, HostPortPair)>,
+    tlvs: Vec<Tlv>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    /// The connection was made by the proxy itself, such as for a health check, and the real
+    /// endpoints of the connection should be used.
+    Local,
+    /// The connection was proxied on behalf of a client.
+    Proxy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Protocol {
+    Unspecified,
+    Stream,
+    Datagram,
+}
+
+/// A type-length-value field of a version 2 header.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Tlv {
+    kind: u8,
+    value: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Error)]
+pub enum ProxyProtocolError {
+    #[error("not a PROXY protocol header")]
+    NotProxyProtocol,
+    #[error("header is longer than {0} bytes")]
+    TooLong(usize),
+    #[error("unsupported version {0}")]
+    UnsupportedVersion(u8),
+    #[error("invalid command {0:#x}")]
+    InvalidCommand(u8),
+    #[error("invalid address family or protocol {0:#04x}")]
+    InvalidFamily(u8),
+    #[error("malformed header")]
+    Malformed,
+    #[error("host is not an IP address")]
+    NotIpAddr,
+    #[error("source and destination are of different address families")]
+    AddressFamilyMismatch,
+    #[error("version 1 headers only support TCP")]
+    UnsupportedInV1,
+}
+
+impl ProxyHeader {
+    /// A header for a connection made by the proxy itself.
+    pub fn local() -> Self {
+        ProxyHeader {
+            command: Command::Local,
+            protocol: Protocol::Unspecified,
+            addrs: None,
+            tlvs: Vec::new(),
+        }
+    }
+
+    /// A header for a proxied connection whose endpoints are unknown, written `PROXY UNKNOWN` in
+    /// version 1.
+    pub fn unknown() -> Self {
+        ProxyHeader {
+            command: Command::Proxy,
+            ..Self::local()
+        }
+    }
+
+    /// A header for a connection proxied from `source` to `destination`, which must be addresses of
+    /// the same family. IPv6 zones can't be encoded and are dropped.
+    pub fn proxy(
+        protocol: Protocol,
+        source: HostPortPair,
+        destination: HostPortPair,
+    ) -> Result<Self, ProxyProtocolError> {
+        let (Some(source_ip), Some(destination_ip)) =
+            (source.host().ip_addr(), destination.host().ip_addr())
+        else {
+            return Err(ProxyProtocolError::NotIpAddr);
+        };
+
+        if source_ip.is_ipv4() != destination_ip.is_ipv4() {
+            return Err(ProxyProtocolError::AddressFamilyMismatch);
+        }
+
+        // Zones are dropped here, so that a decoded header compares equal to the original.
+        let unscoped = |pair: HostPortPair, ip| HostPortPair::from((Host::IpAddr(ip), pair.port()));
+
+        Ok(ProxyHeader {
+            command: Command::Proxy,
+            protocol,
+            addrs: Some((
+                unscoped(source, source_ip),
+                unscoped(destination, destination_ip),
+            )),
+            tlvs: Vec::new(),
+        })
+    }
+
+    /// Adds a TLV, which only version 2 headers can carry.
+    pub fn with_tlv(mut self, tlv: Tlv) -> Self {
+        self.tlvs.push(tlv);
+        self
+    }
+
+    pub fn command(&self) -> Command {
+        self.command
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    /// Returns the original source, or `None` if the header has no addresses or its addresses
+    /// are Unix socket paths.
+    pub fn source(&self) -> Option<&HostPortPair> {
+        self.addrs.as_ref().map(|(source, _)| source)
+    }
+
+    /// Returns the original destination, or `None` if the header has no addresses or its
+    /// addresses are Unix socket paths.
+    pub fn destination(&self) -> Option<&HostPortPair> {
+        self.addrs.as_ref().map(|(_, destination)| destination)
+    }
+
+    pub fn tlvs(&self) -> &[Tlv] {
+        &self.tlvs
+    }
+
+    /// Returns the first TLV of type `kind`.
+    pub fn tlv(&self, kind: u8) -> Option<&Tlv> {
+        self.tlvs.iter().find(|tlv| tlv.kind == kind)
+    }
+
+    /// Decodes a version 1 or version 2 header from the start of `bytes`, returning the header and
+    /// its length, or `None` if more bytes are needed.
+    ///
+    /// Bytes that can't start a header are rejected as soon as they arrive. The CRC32C checksum
+    /// TLV is not verified, and the address block of a `LOCAL` header is skipped.
+    pub fn decode(bytes: &[u8]) -> Result<Option<(Self, usize)>, ProxyProtocolError> {
+        Self::decode_with_max_len(bytes, MAX_V2_LEN)
+    }
+
+    /// Like [`decode`](Self::decode), but rejects version 2 headers longer than `max_len` bytes
+    /// before waiting for the rest of them.
+    pub fn decode_with_max_len(
+        bytes: &[u8],
+        max_len: usize,
+    ) -> Result<Option<(Self, usize)>, ProxyProtocolError> {
+        if bytes.starts_with(V1_PREFIX) {
+            decode_v1(bytes)
+        } else if bytes.starts_with(V2_SIGNATURE) {
+            decode_v2(bytes, max_len)
+        } else if V1_PREFIX.starts_with(bytes) || V2_SIGNATURE.starts_with(bytes) {
+            Ok(None)
+        } else {
+            Err(ProxyProtocolError::NotProxyProtocol)
+        }
+    }
+
+    /// Encodes the header in the text format of version 1.
+    ///
+    /// A `LOCAL` header is encoded as `PROXY UNKNOWN`, which receivers treat the same way. TLVs
+    /// are dropped, and datagram headers are rejected.
+    pub fn to_v1(&self) -> Result<String, ProxyProtocolError> {
+        let Some((source, destination)) = self.addrs.as_ref().filter(|_| self.is_proxy()) else {
+            return Ok(String::from("PROXY UNKNOWN\r\n"));
+        };
+
+        if self.protocol == Protocol::Datagram {
+            return Err(ProxyProtocolError::UnsupportedInV1);
+        }
+
+        let family = match source.host() {
+            Host::IpAddr(IpAddr::V4(_)) => "TCP4",
+            _ => "TCP6",
+        };
+
+        let mut header = String::with_capacity(MAX_V1_LEN);
+
+        // Writing to a `String` can't fail, and addresses are written without brackets.
+        let _ = write!(
+            header,
+            "PROXY {family} {} {} {} {}\r\n",
+            source.host(),
+            destination.host(),
+            source.port(),
+            destination.port(),
+        );
+
+        Ok(header)
+    }
+
+    /// Encodes the header in the binary format of version 2.
+    pub fn to_v2(&self) -> Result<Vec<u8>, ProxyProtocolError> {
+        let mut header = Vec::with_capacity(V2_HEADER_LEN + V2_INET6_LEN);
+        header.extend_from_slice(V2_SIGNATURE);
+
+        header.push(match self.command {
+            Command::Local => 0x20,
+            Command::Proxy => 0x21,
+        });
+
+        let family = match self
+            .addrs
+            .as_ref()
+            .and_then(|(source, _)| source.host().ip_addr())
+        {
+            Some(IpAddr::V4(_)) => 0x10,
+            Some(IpAddr::V6(_)) => 0x20,
+            None => 0x00,
+        };
+
+        let protocol = match self.protocol {
+            Protocol::Unspecified => 0x00,
+            Protocol::Stream => 0x01,
+            Protocol::Datagram => 0x02,
+        };
+
+        header.push(family | protocol);
+        header.extend_from_slice(&[0, 0]);
+
+        if let Some((source, destination)) = &self.addrs {
+            for pair in [source, destination] {
+                match pair.host().ip_addr() {
+                    Some(IpAddr::V4(ip)) => header.extend_from_slice(&ip.octets()),
+                    Some(IpAddr::V6(ip)) => header.extend_from_slice(&ip.octets()),
+                    None => return Err(ProxyProtocolError::NotIpAddr),
+                }
+            }
+
+            header.extend_from_slice(&source.port().to_be_bytes());
+            header.extend_from_slice(&destination.port().to_be_bytes());
+        }
+
+        for tlv in &self.tlvs {
+            let len = u16::try_from(tlv.value.len())
+                .map_err(|_| ProxyProtocolError::TooLong(MAX_V2_LEN))?;
+
+            header.push(tlv.kind);
+            header.extend_from_slice(&len.to_be_bytes());
+            header.extend_from_slice(&tlv.value);
+        }
+
+        let len = u16::try_from(header.len() - V2_HEADER_LEN)
+            .map_err(|_| ProxyProtocolError::TooLong(MAX_V2_LEN))?;
+
+        header[14..16].copy_from_slice(&len.to_be_bytes());
+        Ok(header)
+    }
+
+    fn is_proxy(&self) -> bool {
+        self.command == Command::Proxy
+    }
+}
+
+impl Tlv {
+    pub const ALPN: u8 = 0x01;
+    pub const AUTHORITY: u8 = 0x02;
+    pub const CRC32C: u8 = 0x03;
+    pub const NOOP: u8 = 0x04;
+    pub const UNIQUE_ID: u8 = 0x05;
+    pub const SSL: u8 = 0x20;
+    pub const NETNS: u8 = 0x30;
+
+    pub fn new(kind: u8, value: impl Into<Vec<u8>>) -> Self {
+        Tlv {
+            kind,
+            value: value.into(),
+        }
+    }
+
+    pub fn kind(&self) -> u8 {
+        self.kind
+    }
+
+    pub fn value(&self) -> &[u8] {
+        &self.value
+    }
+}
+
+fn decode_v1(bytes: &[u8]) -> Result<Option<(ProxyHeader, usize)>, ProxyProtocolError> {
+    let Some(newline) = bytes.iter().take(MAX_V1_LEN).position(|&b| b == b'\n') else {
+        return match bytes.len() {
+            len if len >= MAX_V1_LEN => Err(ProxyProtocolError::TooLong(MAX_V1_LEN)),
+            _ => Ok(None),
+        };
+    };
+
+    let line = bytes[..newline]
+        .strip_suffix(b"\r")
+        .and_then(|line| core::str::from_utf8(line).ok())
+        .ok_or(ProxyProtocolError::Malformed)?;
+
+    let mut fields = line[V1_PREFIX.len()..].split(' ');
+    let len = newline + 1;
+
+    // Anything may follow `UNKNOWN`, and receivers must ignore it.
+    let is_ipv4 = match fields.next() {
+        Some("TCP4") => true,
+        Some("TCP6") => false,
+        Some("UNKNOWN") => return Ok(Some((ProxyHeader::unknown(), len))),
+        _ => return Err(ProxyProtocolError::Malformed),
+    };
+
+    let mut ip = || {
+        let ip = fields.next()?;
+
+        match is_ipv4 {
+            true => ip.parse::<Ipv4Addr>().ok().map(IpAddr::V4),
+            false => ip.parse::<Ipv6Addr>().ok().map(IpAddr::V6),
+        }
+    };
+
+    let (source_ip, destination_ip) = (ip(), ip());
+
+    let mut port = || {
+        let port = fields.next()?;
+
+        // Ports are plain decimal numbers, without signs or leading zeros.
+        if port.is_empty() || !port.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+
+        if port.len() > 1 && port.starts_with('0') {
+            return None;
+        }
+
+        port.parse::<u16>().ok()
+    };
+
+    let (source_port, destination_port) = (port(), port());
+
+    let (Some(source_ip), Some(destination_ip), Some(source_port), Some(destination_port), None) = (
+        source_ip,
+        destination_ip,
+        source_port,
+        destination_port,
+        fields.next(),
+    ) else {
+        return Err(ProxyProtocolError::Malformed);
+    };
+
+    let header = ProxyHeader {
+        command: Command::Proxy,
+        protocol: Protocol::Stream,
+        addrs: Some((
+            HostPortPair::from((source_ip, source_port)),
+            HostPortPair::from((destination_ip, destination_port)),
+        )),
+        tlvs: Vec::new(),
+    };
+
+    Ok(Some((header, len)))
+}
+
+fn decode_v2(
+    bytes: &[u8],
+    max_len: usize,
+) -> Result<Option<(ProxyHeader, usize)>, ProxyProtocolError> {
+    let Some(fixed) = bytes.get(..V2_HEADER_LEN) else {
+        return Ok(None);
+    };
+
+    let version = fixed[12] >> 4;
+    if version != 2 {
+        return Err(ProxyProtocolError::UnsupportedVersion(version));
+    }
+
+    let command = match fixed[12] & 0x0f {
+        0x0 => Command::Local,
+        0x1 => Command::Proxy,
+        command => return Err(ProxyProtocolError::InvalidCommand(command)),
+    };
+
+    let protocol = match fixed[13] & 0x0f {
+        0x0 => Protocol::Unspecified,
+        0x1 => Protocol::Stream,
+        0x2 => Protocol::Datagram,
+        _ => return Err(ProxyProtocolError::InvalidFamily(fixed[13])),
+    };
+
+    let addr_len = match fixed[13] >> 4 {
+        0x0 => 0,
+        0x1 => V2_INET_LEN,
+        0x2 => V2_INET6_LEN,
+        0x3 => V2_UNIX_LEN,
+        _ => return Err(ProxyProtocolError::InvalidFamily(fixed[13])),
+    };
+
+    let len = V2_HEADER_LEN + usize::from(u16::from_be_bytes([fixed[14], fixed[15]]));
+
+    if len > max_len {
+        return Err(ProxyProtocolError::TooLong(max_len));
+    }
+
+    let Some(block) = bytes.get(V2_HEADER_LEN..len) else {
+        return Ok(None);
+    };
+
+    if command == Command::Local {
+        return Ok(Some((ProxyHeader::local(), len)));
+    }
+
+    if block.len() < addr_len {
+        return Err(ProxyProtocolError::Malformed);
+    }
+
+    let (addrs, tlvs) = block.split_at(addr_len);
+
+    let addrs = match addr_len {
+        V2_INET_LEN => Some(decode_v2_addrs::<4>(addrs, |octets| {
+            IpAddr::V4(Ipv4Addr::from(octets))
+        })),
+        V2_INET6_LEN => Some(decode_v2_addrs::<16>(addrs, |octets| {
+            IpAddr::V6(Ipv6Addr::from(octets))
+        })),
+        // Unspecified and Unix socket addresses can't be represented as host-port pairs.
+        _ => None,
+    };
+
+    let header = ProxyHeader {
+        command,
+        protocol,
+        addrs,
+        tlvs: decode_tlvs(tlvs)?,
+    };
+
+    Ok(Some((header, len)))
+}
+
+// Decodes the source and destination addresses, followed by the source and destination ports.
+fn decode_v2_addrs<const N: usize>(
+    block: &[u8],
+    ip: impl Fn([u8; N]) -> IpAddr,
+) -> (HostPortPair, HostPortPair) {
+    let octets = |start: usize| <[u8; N]>::try_from(&block[start..start + N]).unwrap();
+    let port = |start: usize| u16::from_be_bytes([block[start], block[start + 1]]);
+
+    (
+        HostPortPair::from((ip(octets(0)), port(2 * N))),
+        HostPortPair::from((ip(octets(N)), port(2 * N + 2))),
+    )
+}
+
+fn decode_tlvs(mut bytes: &[u8]) -> Result<Vec<Tlv>, ProxyProtocolError> {
+    let mut tlvs = Vec::new();
+
+    while let Some((&[kind, high, low], rest)) = bytes.split_first_chunk::<3>() {
+        let len = usize::from(u16::from_be_bytes([high, low]));
+        let value = rest.get(..len).ok_or(ProxyProtocolError::Malformed)?;
+
+        tlvs.push(Tlv::new(kind, value));
+        bytes = &rest[len..];
+    }
+
+    match bytes {
+        [] => Ok(tlvs),
+        _ => Err(ProxyProtocolError::Malformed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(s: &str) -> HostPortPair {
+        HostPortPair::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn v1_round_trip() {
+        let header = ProxyHeader::proxy(
+            Protocol::Stream,
+            pair("192.0.2.1:56324"),
+            pair("198.51.100.1:443"),
+        )
+        .unwrap();
+
+        let line = header.to_v1().unwrap();
+        assert_eq!(line, "PROXY TCP4 192.0.2.1 198.51.100.1 56324 443\r\n");
+
+        let mut bytes = line.clone().into_bytes();
+        bytes.extend_from_slice(b"GET / HTTP/1.1\r\n");
+        assert_eq!(
+            ProxyHeader::decode(&bytes).unwrap(),
+            Some((header, line.len()))
+        );
+
+        let header = ProxyHeader::proxy(
+            Protocol::Stream,
+            pair("[fe80::1%25eth0]:1"),
+            pair("[::1]:2"),
+        )
+        .unwrap();
+
+        let line = header.to_v1().unwrap();
+        assert_eq!(line, "PROXY TCP6 fe80::1 ::1 1 2\r\n");
+        assert_eq!(
+            ProxyHeader::decode(line.as_bytes()).unwrap(),
+            Some((header, line.len()))
+        );
+    }
+
+    #[test]
+    fn v1_unknown() {
+        assert_eq!(ProxyHeader::local().to_v1().unwrap(), "PROXY UNKNOWN\r\n");
+
+        let bytes = b"PROXY UNKNOWN ignored\r\n";
+        assert_eq!(
+            ProxyHeader::decode(bytes).unwrap(),
+            Some((ProxyHeader::unknown(), bytes.len()))
+        );
+    }
+
+    #[test]
+    fn v1_errors() {
+        let decode = |bytes: &[u8]| ProxyHeader::decode(bytes).map(|header| header.map(|_| ()));
+
+        assert_eq!(decode(b"PRO"), Ok(None));
+        assert_eq!(decode(b"PROXY TCP4 192.0.2.1"), Ok(None));
+        assert_eq!(
+            decode(b"GET / HTTP/1.1\r\n"),
+            Err(ProxyProtocolError::NotProxyProtocol)
+        );
+        assert_eq!(
+            decode(b"PROXY TCP4 192.0.2.1 ::1 1 2\r\n"),
+            Err(ProxyProtocolError::Malformed)
+        );
+        assert_eq!(
+            decode(b"PROXY TCP4 192.0.2.1 192.0.2.2 01 2\r\n"),
+            Err(ProxyProtocolError::Malformed)
+        );
+        assert_eq!(
+            decode(b"PROXY TCP4 192.0.2.1 192.0.2.2 1 2\n"),
+            Err(ProxyProtocolError::Malformed)
+        );
+
+        let mut long = V1_PREFIX.to_vec();
+        long.resize(MAX_V1_LEN, b' ');
+        assert_eq!(decode(&long), Err(ProxyProtocolError::TooLong(MAX_V1_LEN)));
+
+        let datagram =
+            ProxyHeader::proxy(Protocol::Datagram, pair("192.0.2.1:1"), pair("192.0.2.2:2"))
+                .unwrap();
+        assert_eq!(datagram.to_v1(), Err(ProxyProtocolError::UnsupportedInV1));
+    }
+
+    #[test]
+    fn v2_round_trip() {
+        let header = ProxyHeader::proxy(
+            Protocol::Stream,
+            pair("192.0.2.1:56324"),
+            pair("198.51.100.1:443"),
+        )
+        .unwrap()
+        .with_tlv(Tlv::new(Tlv::AUTHORITY, "example.com"));
+
+        let bytes = header.to_v2().unwrap();
+        assert_eq!(&bytes[..12], V2_SIGNATURE);
+        assert_eq!(bytes[12..16], [0x21, 0x11, 0, 12 + 3 + 11]);
+        assert_eq!(&bytes[16..20], &[192, 0, 2, 1]);
+
+        let (decoded, len) = ProxyHeader::decode(&bytes).unwrap().unwrap();
+        assert_eq!(len, bytes.len());
+        assert_eq!(decoded, header);
+        assert_eq!(
+            decoded.tlv(Tlv::AUTHORITY).map(Tlv::value),
+            Some(&b"example.com"[..])
+        );
+
+        let header = ProxyHeader::proxy(
+            Protocol::Datagram,
+            pair("[2001:db8::1]:53"),
+            pair("[2001:db8::2]:53"),
+        )
+        .unwrap();
+
+        let bytes = header.to_v2().unwrap();
+        assert_eq!(bytes.len(), V2_HEADER_LEN + V2_INET6_LEN);
+        assert_eq!(
+            ProxyHeader::decode(&bytes).unwrap(),
+            Some((header, bytes.len()))
+        );
+
+        let bytes = ProxyHeader::local().to_v2().unwrap();
+        assert_eq!(
+            ProxyHeader::decode(&bytes).unwrap(),
+            Some((ProxyHeader::local(), V2_HEADER_LEN))
+        );
+    }
+
+    #[test]
+    fn v2_errors() {
+        let header =
+            ProxyHeader::proxy(Protocol::Stream, pair("192.0.2.1:1"), pair("192.0.2.2:2")).unwrap();
+        let bytes = header.to_v2().unwrap();
+
+        for len in 0..bytes.len() {
+            assert_eq!(ProxyHeader::decode(&bytes[..len]), Ok(None), "{len}");
+        }
+
+        let with = |index: usize, byte: u8| {
+            let mut bytes = bytes.clone();
+            bytes[index] = byte;
+            ProxyHeader::decode(&bytes).map(|header| header.map(|_| ()))
+        };
+
+        assert_eq!(
+            with(12, 0x11),
+            Err(ProxyProtocolError::UnsupportedVersion(1))
+        );
+        assert_eq!(with(12, 0x22), Err(ProxyProtocolError::InvalidCommand(2)));
+        assert_eq!(with(13, 0x41), Err(ProxyProtocolError::InvalidFamily(0x41)));
+        assert_eq!(with(15, 4), Err(ProxyProtocolError::Malformed));
+
+        assert_eq!(
+            ProxyHeader::decode_with_max_len(&bytes, V2_HEADER_LEN),
+            Err(ProxyProtocolError::TooLong(V2_HEADER_LEN))
+        );
+
+        assert_eq!(
+            ProxyHeader::proxy(Protocol::Stream, pair("192.0.2.1:1"), pair("[::1]:2")),
+            Err(ProxyProtocolError::AddressFamilyMismatch)
+        );
+        assert_eq!(
+            ProxyHeader::proxy(Protocol::Stream, pair("example.com:1"), pair("[::1]:2")),
+            Err(ProxyProtocolError::NotIpAddr)
+        );
+    }
+}
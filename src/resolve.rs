@@ -0,0 +1,300 @@
+use crate::{hosts::HostsFile, Host, HostPortPair, SocketAddrError};
+use std::{
+    collections::HashMap,
+    io::{Error as IoError, ErrorKind},
+    net::{IpAddr, SocketAddr, ToSocketAddrs},
+    thread,
+    time::Duration,
+    vec::IntoIter,
+};
+use thiserror::Error;
+
+#[cfg(feature = "tokio")]
+use crate::{HostPortPairRef, HostRef};
+#[cfg(feature = "tokio")]
+use std::future::Future;
+
+pub use self::{
+    cache::{CacheStats, CachingResolver, Clock, ManualClock, SystemClock},
+    hosts::HostsResolver,
+};
+
+mod cache;
+mod hosts;
+
+#[derive(Debug, Error)]
+pub enum ResolveError {
+    /// The name does not exist.
+    #[error("name not found")]
+    NotFound,
+    /// The resolver answered, but with no addresses.
+    #[error("no addresses found")]
+    NoRecords,
+    #[error("resolution timed out")]
+    Timeout,
+    /// The resolver failed, which is also how the system resolver reports a name that doesn't exist.
+    #[error(transparent)]
+    Io(#[from] IoError),
+}
+
+impl From<ResolveError> for IoError {
+    fn from(err: ResolveError) -> Self {
+        match err {
+            ResolveError::NotFound | ResolveError::NoRecords => {
+                IoError::new(ErrorKind::NotFound, err)
+            }
+            ResolveError::Timeout => IoError::new(ErrorKind::TimedOut, err),
+            ResolveError::Io(err) => err,
+        }
+    }
+}
+
+/// Resolves hosts to IP addresses, blocking the current thread.
+///
+/// IP hosts resolve to themselves. An empty answer is reported as [`ResolveError::NoRecords`].
+pub trait Resolver {
+    fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError>;
+
+    /// Resolves a host-port pair. IP hosts bypass the resolver and keep their IPv6 zone.
+    fn resolve_socket_addrs(
+        &self,
+        pair: &HostPortPair,
+    ) -> Result<IntoIter<SocketAddr>, ResolveError> {
+        if let Some(addr) = literal_socket_addr(pair)? {
+            return Ok(vec![addr].into_iter());
+        }
+
+        let ips = self.resolve(pair.host())?;
+        Ok(socket_addrs(ips, pair.port()))
+    }
+}
+
+/// The asynchronous flavor of [`Resolver`].
+#[cfg(feature = "tokio")]
+pub trait AsyncResolver {
+    fn resolve(
+        &self,
+        host: &Host,
+    ) -> impl Future<Output = Result<Vec<IpAddr>, ResolveError>> + Send;
+
+    /// Resolves a host-port pair. IP hosts bypass the resolver and keep their IPv6 zone.
+    fn resolve_socket_addrs(
+        &self,
+        pair: &HostPortPair,
+    ) -> impl Future<Output = Result<IntoIter<SocketAddr>, ResolveError>> + Send
+    where
+        Self: Sync,
+    {
+        async move {
+            if let Some(addr) = literal_socket_addr(pair)? {
+                return Ok(vec![addr].into_iter());
+            }
+
+            let ips = self.resolve(pair.host()).await?;
+            Ok(socket_addrs(ips, pair.port()))
+        }
+    }
+}
+
+/// The resolver of the operating system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        match host {
+            Host::DnsName(name) => {
+                let addrs = (name.as_str(), 0).to_socket_addrs()?;
+                non_empty(addrs.map(|addr| addr.ip()).collect())
+            }
+            _ => Ok(host.ip_addr().into_iter().collect()),
+        }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncResolver for SystemResolver {
+    async fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        match host {
+            Host::DnsName(name) => {
+                let addrs = tokio::net::lookup_host((name.as_str(), 0)).await?;
+                non_empty(addrs.map(|addr| addr.ip()).collect())
+            }
+            _ => Ok(host.ip_addr().into_iter().collect()),
+        }
+    }
+}
+
+/// A resolver that answers from a fixed table, for tests.
+///
+/// Names missing from the table are reported as [`ResolveError::NotFound`].
+#[derive(Debug, Clone, Default)]
+pub struct StaticResolver {
+    answers: HashMap<Host, Answer>,
+    latency: Duration,
+}
+
+#[derive(Debug, Clone)]
+enum Answer {
+    Addrs(Vec<IpAddr>),
+    NotFound,
+}
+
+impl StaticResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a resolver from text in the `/etc/hosts` format. Lines without a valid address are
+    /// skipped.
+    pub fn from_hosts(text: &str) -> Self {
+        let mut resolver = Self::new();
+
+        for entry in HostsFile::parse(text).entries() {
+            for name in entry.names() {
+                let answer = resolver.answers.entry(name.clone());
+
+                if let Answer::Addrs(addrs) = answer.or_insert(Answer::Addrs(Vec::new())) {
+                    addrs.push(entry.ip());
+                }
+            }
+        }
+
+        resolver
+    }
+
+    /// Answers `host` with `addrs`, replacing any previous answer.
+    pub fn with_addrs(
+        mut self,
+        host: impl Into<Host>,
+        addrs: impl IntoIterator<Item = IpAddr>,
+    ) -> Self {
+        let addrs = addrs.into_iter().collect();
+        self.answers.insert(host.into(), Answer::Addrs(addrs));
+        self
+    }
+
+    /// Answers `host` with NXDOMAIN, replacing any previous answer.
+    pub fn with_not_found(mut self, host: impl Into<Host>) -> Self {
+        self.answers.insert(host.into(), Answer::NotFound);
+        self
+    }
+
+    /// Delays every lookup of a DNS name by `latency`.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    fn answer(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        match self.answers.get(host) {
+            Some(Answer::Addrs(addrs)) => non_empty(addrs.clone()),
+            Some(Answer::NotFound) | None => Err(ResolveError::NotFound),
+        }
+    }
+}
+
+impl Resolver for StaticResolver {
+    fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        if let Some(ip) = host.ip_addr() {
+            return Ok(vec![ip]);
+        }
+
+        if !self.latency.is_zero() {
+            thread::sleep(self.latency);
+        }
+
+        self.answer(host)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncResolver for StaticResolver {
+    async fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        if let Some(ip) = host.ip_addr() {
+            return Ok(vec![ip]);
+        }
+
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+
+        self.answer(host)
+    }
+}
+
+// IP hosts bypass resolvers, which also keeps the zone of scoped IPv6 addresses.
+fn literal_socket_addr(pair: &HostPortPair) -> Result<Option<SocketAddr>, ResolveError> {
+    match pair.host() {
+        Host::DnsName(_) => Ok(None),
+        _ => match pair.as_socket_addr() {
+            Some(addr) => Ok(Some(addr)),
+            None => Err(unknown_zone()),
+        },
+    }
+}
+
+fn socket_addrs(ips: Vec<IpAddr>, port: u16) -> IntoIter<SocketAddr> {
+    ips.into_iter()
+        .map(|ip| SocketAddr::new(ip, port))
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+fn non_empty(ips: Vec<IpAddr>) -> Result<Vec<IpAddr>, ResolveError> {
+    if ips.is_empty() {
+        Err(ResolveError::NoRecords)
+    } else {
+        Ok(ips)
+    }
+}
+
+fn unknown_zone() -> ResolveError {
+    ResolveError::Io(IoError::new(
+        ErrorKind::InvalidInput,
+        SocketAddrError::UnknownZone,
+    ))
+}
+
+#[cfg(feature = "tokio")]
+impl HostPortPairRef<'_> {
+    /// Resolves the pair with the system resolver. IP hosts are returned without a lookup.
+    pub async fn resolve(&self) -> Result<IntoIter<SocketAddr>, ResolveError> {
+        let addrs = match self.host {
+            HostRef::DnsName(name) => tokio::net::lookup_host((name, self.port))
+                .await?
+                .collect::<Vec<_>>(),
+            _ => vec![self.as_socket_addr().ok_or_else(unknown_zone)?],
+        };
+
+        if addrs.is_empty() {
+            return Err(ResolveError::NoRecords);
+        }
+
+        Ok(addrs.into_iter())
+    }
+
+    pub async fn resolve_with_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<IntoIter<SocketAddr>, ResolveError> {
+        tokio::time::timeout(timeout, self.resolve())
+            .await
+            .map_err(|_| ResolveError::Timeout)?
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl HostPortPair {
+    /// Resolves the pair with the system resolver. IP hosts are returned without a lookup.
+    pub async fn resolve(&self) -> Result<IntoIter<SocketAddr>, ResolveError> {
+        self.as_borrowed().resolve().await
+    }
+
+    pub async fn resolve_with_timeout(
+        &self,
+        timeout: Duration,
+    ) -> Result<IntoIter<SocketAddr>, ResolveError> {
+        self.as_borrowed().resolve_with_timeout(timeout).await
+    }
+}
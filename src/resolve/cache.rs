@@ -0,0 +1,440 @@
+use super::{ResolveError, Resolver};
+use crate::Host;
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::{Error as IoError, ErrorKind},
+    net::IpAddr,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "tokio")]
+use super::AsyncResolver;
+
+/// A source of the current time, so that cache expiry can be tested without waiting.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that only moves when advanced. Clones share the same time.
+#[derive(Debug, Clone)]
+pub struct ManualClock(Arc<Mutex<Instant>>);
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock(Arc::new(Mutex::new(Instant::now())))
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *lock(&self.0) += by;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *lock(&self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+/// Caches the answers of another resolver.
+///
+/// Addresses are kept for the TTL and failures for the negative TTL. When the cache is full, the
+/// least recently used entry is evicted. IP hosts are never cached.
+#[derive(Debug)]
+pub struct CachingResolver<R, C = SystemClock> {
+    inner: R,
+    clock: C,
+    ttl: Duration,
+    negative_ttl: Duration,
+    capacity: usize,
+    cache: Mutex<Cache>,
+}
+
+#[derive(Debug, Default)]
+struct Cache {
+    entries: HashMap<Host, Entry>,
+    // Hosts by the tick of their last use, oldest first.
+    lru: BTreeMap<u64, Host>,
+    tick: u64,
+    stats: CacheStats,
+}
+
+#[derive(Debug)]
+struct Entry {
+    answer: Result<Vec<IpAddr>, CachedError>,
+    // `None` if the TTL is too long to represent.
+    expires: Option<Instant>,
+    last_used: u64,
+}
+
+// `ResolveError` can't be cloned because of its `io::Error`, so failures are stored like this.
+#[derive(Debug, Clone)]
+enum CachedError {
+    NotFound,
+    NoRecords,
+    Timeout,
+    Io(ErrorKind, String),
+}
+
+impl<R> CachingResolver<R> {
+    /// Wraps `inner` with a TTL of 60 seconds, a negative TTL of 5 seconds and room for 1024 hosts.
+    pub fn new(inner: R) -> Self {
+        CachingResolver {
+            inner,
+            clock: SystemClock,
+            ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(5),
+            capacity: 1024,
+            cache: Mutex::default(),
+        }
+    }
+}
+
+impl<R, C> CachingResolver<R, C> {
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Sets how long failures are cached. A zero duration disables negative caching.
+    pub fn with_negative_ttl(mut self, negative_ttl: Duration) -> Self {
+        self.negative_ttl = negative_ttl;
+        self
+    }
+
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn with_clock<C2: Clock>(self, clock: C2) -> CachingResolver<R, C2> {
+        CachingResolver {
+            inner: self.inner,
+            clock,
+            ttl: self.ttl,
+            negative_ttl: self.negative_ttl,
+            capacity: self.capacity,
+            cache: self.cache,
+        }
+    }
+
+    pub fn inner(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        lock(&self.cache).stats
+    }
+
+    /// Returns the number of cached hosts, including expired ones that haven't been evicted yet.
+    pub fn len(&self) -> usize {
+        lock(&self.cache).entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn clear(&self) {
+        let mut cache = lock(&self.cache);
+        cache.entries.clear();
+        cache.lru.clear();
+    }
+}
+
+impl<R, C: Clock> CachingResolver<R, C> {
+    fn get(&self, host: &Host) -> Option<Result<Vec<IpAddr>, ResolveError>> {
+        lock(&self.cache).get(host, self.clock.now())
+    }
+
+    fn insert(&self, host: &Host, answer: &Result<Vec<IpAddr>, ResolveError>) {
+        let (answer, ttl) = match answer {
+            Ok(addrs) => (Ok(addrs.clone()), self.ttl),
+            Err(err) => (Err(CachedError::new(err)), self.negative_ttl),
+        };
+
+        if self.capacity == 0 || ttl.is_zero() {
+            return;
+        }
+
+        let expires = self.clock.now().checked_add(ttl);
+        lock(&self.cache).insert(host.clone(), answer, expires, self.capacity);
+    }
+}
+
+impl<R: Resolver, C: Clock> Resolver for CachingResolver<R, C> {
+    fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        if let Some(ip) = host.ip_addr() {
+            return Ok(vec![ip]);
+        }
+
+        if let Some(answer) = self.get(host) {
+            return answer;
+        }
+
+        let answer = self.inner.resolve(host);
+        self.insert(host, &answer);
+        answer
+    }
+}
+
+// Concurrent misses for the same host each query the inner resolver, as the lock isn't held
+// across the lookup.
+#[cfg(feature = "tokio")]
+impl<R, C> AsyncResolver for CachingResolver<R, C>
+where
+    R: AsyncResolver + Sync,
+    C: Clock + Sync,
+{
+    async fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        if let Some(ip) = host.ip_addr() {
+            return Ok(vec![ip]);
+        }
+
+        if let Some(answer) = self.get(host) {
+            return answer;
+        }
+
+        let answer = self.inner.resolve(host).await;
+        self.insert(host, &answer);
+        answer
+    }
+}
+
+impl Cache {
+    fn get(&mut self, host: &Host, now: Instant) -> Option<Result<Vec<IpAddr>, ResolveError>> {
+        let Some(entry) = self.entries.get_mut(host) else {
+            self.stats.misses += 1;
+            return None;
+        };
+
+        self.lru.remove(&entry.last_used);
+
+        if entry.expires.is_some_and(|expires| expires <= now) {
+            self.entries.remove(host);
+            self.stats.misses += 1;
+            return None;
+        }
+
+        self.tick += 1;
+        entry.last_used = self.tick;
+        self.lru.insert(self.tick, host.clone());
+        self.stats.hits += 1;
+
+        Some(match &entry.answer {
+            Ok(addrs) => Ok(addrs.clone()),
+            Err(err) => Err(err.to_error()),
+        })
+    }
+
+    fn insert(
+        &mut self,
+        host: Host,
+        answer: Result<Vec<IpAddr>, CachedError>,
+        expires: Option<Instant>,
+        capacity: usize,
+    ) {
+        if let Some(entry) = self.entries.remove(&host) {
+            self.lru.remove(&entry.last_used);
+        }
+
+        while self.entries.len() >= capacity {
+            let Some((_, oldest)) = self.lru.pop_first() else {
+                break;
+            };
+
+            self.entries.remove(&oldest);
+            self.stats.evictions += 1;
+        }
+
+        self.tick += 1;
+        self.lru.insert(self.tick, host.clone());
+
+        let entry = Entry {
+            answer,
+            expires,
+            last_used: self.tick,
+        };
+
+        self.entries.insert(host, entry);
+    }
+}
+
+impl CachedError {
+    fn new(err: &ResolveError) -> Self {
+        match err {
+            ResolveError::NotFound => CachedError::NotFound,
+            ResolveError::NoRecords => CachedError::NoRecords,
+            ResolveError::Timeout => CachedError::Timeout,
+            ResolveError::Io(err) => CachedError::Io(err.kind(), err.to_string()),
+        }
+    }
+
+    fn to_error(&self) -> ResolveError {
+        match self {
+            CachedError::NotFound => ResolveError::NotFound,
+            CachedError::NoRecords => ResolveError::NoRecords,
+            CachedError::Timeout => ResolveError::Timeout,
+            CachedError::Io(kind, message) => {
+                ResolveError::Io(IoError::new(*kind, message.clone()))
+            }
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resolve::StaticResolver;
+    use std::net::Ipv4Addr;
+
+    const ADDR: IpAddr = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+    fn resolver(clock: &ManualClock) -> CachingResolver<StaticResolver, ManualClock> {
+        let inner = StaticResolver::new()
+            .with_addrs("a.example", [ADDR])
+            .with_addrs("b.example", [ADDR])
+            .with_addrs("c.example", [ADDR])
+            .with_not_found("missing.example");
+
+        CachingResolver::new(inner)
+            .with_ttl(Duration::from_secs(60))
+            .with_negative_ttl(Duration::from_secs(5))
+            .with_clock(clock.clone())
+    }
+
+    fn stats(hits: u64, misses: u64, evictions: u64) -> CacheStats {
+        CacheStats {
+            hits,
+            misses,
+            evictions,
+        }
+    }
+
+    #[test]
+    fn positive_ttl() {
+        let clock = ManualClock::new();
+        let resolver = resolver(&clock);
+        let host = Host::from("a.example");
+
+        assert_eq!(Resolver::resolve(&resolver, &host).unwrap(), [ADDR]);
+        assert_eq!(Resolver::resolve(&resolver, &host).unwrap(), [ADDR]);
+        assert_eq!(resolver.stats(), stats(1, 1, 0));
+
+        clock.advance(Duration::from_secs(59));
+        Resolver::resolve(&resolver, &host).unwrap();
+        assert_eq!(resolver.stats(), stats(2, 1, 0));
+
+        clock.advance(Duration::from_secs(1));
+        Resolver::resolve(&resolver, &host).unwrap();
+        assert_eq!(resolver.stats(), stats(2, 2, 0));
+        assert_eq!(resolver.len(), 1);
+    }
+
+    #[test]
+    fn negative_ttl() {
+        let clock = ManualClock::new();
+        let resolver = resolver(&clock);
+        let host = Host::from("missing.example");
+
+        assert!(matches!(
+            Resolver::resolve(&resolver, &host),
+            Err(ResolveError::NotFound)
+        ));
+        assert!(matches!(
+            Resolver::resolve(&resolver, &host),
+            Err(ResolveError::NotFound)
+        ));
+        assert_eq!(resolver.stats(), stats(1, 1, 0));
+
+        clock.advance(Duration::from_secs(5));
+        assert!(Resolver::resolve(&resolver, &host).is_err());
+        assert_eq!(resolver.stats(), stats(1, 2, 0));
+    }
+
+    #[test]
+    fn zero_negative_ttl() {
+        let clock = ManualClock::new();
+        let resolver = resolver(&clock).with_negative_ttl(Duration::ZERO);
+        let host = Host::from("missing.example");
+
+        assert!(Resolver::resolve(&resolver, &host).is_err());
+        assert!(Resolver::resolve(&resolver, &host).is_err());
+        assert_eq!(resolver.stats(), stats(0, 2, 0));
+        assert!(resolver.is_empty());
+
+        Resolver::resolve(&resolver, &Host::from("a.example")).unwrap();
+        assert_eq!(resolver.len(), 1);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let clock = ManualClock::new();
+        let resolver = resolver(&clock).with_capacity(2);
+        let [a, b, c] = ["a.example", "b.example", "c.example"].map(Host::from);
+
+        Resolver::resolve(&resolver, &a).unwrap();
+        Resolver::resolve(&resolver, &b).unwrap();
+        // Using `a` makes `b` the least recently used.
+        Resolver::resolve(&resolver, &a).unwrap();
+        Resolver::resolve(&resolver, &c).unwrap();
+        assert_eq!(resolver.stats(), stats(1, 3, 1));
+        assert_eq!(resolver.len(), 2);
+
+        Resolver::resolve(&resolver, &a).unwrap();
+        Resolver::resolve(&resolver, &c).unwrap();
+        assert_eq!(resolver.stats(), stats(3, 3, 1));
+
+        Resolver::resolve(&resolver, &b).unwrap();
+        assert_eq!(resolver.stats(), stats(3, 4, 2));
+    }
+
+    #[test]
+    fn ip_hosts_skip_cache() {
+        let clock = ManualClock::new();
+        let resolver = resolver(&clock);
+
+        assert_eq!(
+            Resolver::resolve(&resolver, &Host::IpAddr(ADDR)).unwrap(),
+            [ADDR]
+        );
+        assert_eq!(resolver.stats(), CacheStats::default());
+        assert!(resolver.is_empty());
+    }
+
+    #[test]
+    fn names_are_case_insensitive() {
+        let clock = ManualClock::new();
+        let resolver = resolver(&clock);
+
+        Resolver::resolve(&resolver, &Host::from("a.example")).unwrap();
+        Resolver::resolve(&resolver, &Host::from("A.Example.")).unwrap();
+        assert_eq!(resolver.stats(), stats(1, 1, 0));
+    }
+}
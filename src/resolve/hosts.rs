@@ -0,0 +1,77 @@
+use super::{non_empty, ResolveError, Resolver};
+use crate::{hosts::HostsFile, Host};
+use std::{collections::HashMap, io::Result as IoResult, net::IpAddr, path::Path};
+
+#[cfg(feature = "tokio")]
+use super::AsyncResolver;
+
+/// A resolver that answers from a hosts file, without DNS.
+///
+/// Names missing from the file are reported as [`ResolveError::NotFound`].
+#[derive(Debug, Clone, Default)]
+pub struct HostsResolver {
+    addrs: HashMap<Host, Vec<IpAddr>>,
+    names: HashMap<IpAddr, Vec<Host>>,
+}
+
+impl HostsResolver {
+    pub fn new(file: &HostsFile) -> Self {
+        let mut resolver = Self::default();
+
+        for entry in file.entries() {
+            for name in entry.names() {
+                let addrs = resolver.addrs.entry(name.clone()).or_default();
+
+                if !addrs.contains(&entry.ip()) {
+                    addrs.push(entry.ip());
+                }
+            }
+
+            let names = resolver.names.entry(entry.ip()).or_default();
+
+            for name in entry.names() {
+                if !names.contains(name) {
+                    names.push(name.clone());
+                }
+            }
+        }
+
+        resolver
+    }
+
+    /// Reads a hosts file, such as `/etc/hosts`.
+    pub fn load(path: impl AsRef<Path>) -> IoResult<Self> {
+        HostsFile::load(path).map(|file| Self::new(&file))
+    }
+
+    /// Returns the names of an IP host, canonical names first. DNS names have none.
+    pub fn reverse(&self, host: &Host) -> &[Host] {
+        host.ip_addr()
+            .and_then(|ip| self.names.get(&ip))
+            .map_or(&[], Vec::as_slice)
+    }
+
+    fn answer(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        if let Some(ip) = host.ip_addr() {
+            return Ok(vec![ip]);
+        }
+
+        match self.addrs.get(host) {
+            Some(addrs) => non_empty(addrs.clone()),
+            None => Err(ResolveError::NotFound),
+        }
+    }
+}
+
+impl Resolver for HostsResolver {
+    fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        self.answer(host)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl AsyncResolver for HostsResolver {
+    async fn resolve(&self, host: &Host) -> Result<Vec<IpAddr>, ResolveError> {
+        self.answer(host)
+    }
+}
@@ -0,0 +1,361 @@
+use crate::{Host, HostPortPair, HostRef, Socks5AddrError};
+use alloc::vec::Vec;
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+#[cfg(feature = "bytes")]
+use bytes::{Buf, BufMut};
+#[cfg(feature = "std")]
+use std::io::{Error as IoError, ErrorKind, Read, Result as IoResult, Write};
+#[cfg(feature = "tokio")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const ATYP_IPV4: u8 = 0x01;
+const ATYP_DOMAIN: u8 = 0x03;
+const ATYP_IPV6: u8 = 0x04;
+
+// The address type, the domain name length, the longest domain name and the port.
+const MAX_LEN: usize = 1 + 1 + 255 + 2;
+
+// SOCKS5 addresses (RFC 1928 section 5): an address type, the address and a big-endian port.
+impl HostPortPair {
+    /// Encodes the pair as a SOCKS5 address. IPv6 zones can't be encoded and are dropped.
+    pub fn to_socks5_addr(&self) -> Result<Vec<u8>, Socks5AddrError> {
+        let (bytes, len) = self.encode_socks5_addr()?;
+        Ok(bytes[..len].to_vec())
+    }
+
+    /// Encodes the pair as a SOCKS5 address into `buf`. IPv6 zones can't be encoded and are
+    /// dropped.
+    #[cfg(feature = "bytes")]
+    pub fn write_socks5_addr(&self, buf: &mut impl BufMut) -> Result<(), Socks5AddrError> {
+        let (bytes, len) = self.encode_socks5_addr()?;
+        buf.put_slice(&bytes[..len]);
+        Ok(())
+    }
+
+    /// Encodes the pair as a SOCKS5 address into `writer`. IPv6 zones can't be encoded and are
+    /// dropped.
+    #[cfg(feature = "std")]
+    pub fn write_socks5_addr_to(&self, writer: &mut impl Write) -> IoResult<()> {
+        let (bytes, len) = self.encode_socks5_addr()?;
+        writer.write_all(&bytes[..len])
+    }
+
+    /// Encodes the pair as a SOCKS5 address into `writer` with `tokio`. IPv6 zones can't be
+    /// encoded and are dropped.
+    #[cfg(feature = "tokio")]
+    pub async fn write_socks5_addr_async(
+        &self,
+        writer: &mut (impl AsyncWrite + Unpin),
+    ) -> IoResult<()> {
+        let (bytes, len) = self.encode_socks5_addr()?;
+        writer.write_all(&bytes[..len]).await
+    }
+
+    /// Decodes a SOCKS5 address from the start of `bytes`, returning the pair and the number of
+    /// bytes it took.
+    ///
+    /// [`Socks5AddrError::Truncated`] means that the address continues past the end of `bytes`.
+    /// Domain names that are IP literals are decoded as addresses, like the `From<&str>`
+    /// conversion of [`Host`].
+    pub fn decode_socks5_addr(bytes: &[u8]) -> Result<(Self, usize), Socks5AddrError> {
+        let atyp = *bytes.first().ok_or(Socks5AddrError::Truncated)?;
+        check_addr_type(atyp)?;
+
+        let second = *bytes.get(1).ok_or(Socks5AddrError::Truncated)?;
+        let len = addr_len(atyp, second)?;
+        let bytes = bytes.get(..len).ok_or(Socks5AddrError::Truncated)?;
+        let (addr, port) = bytes[1..].split_at(len - 3);
+
+        let host = match atyp {
+            ATYP_IPV4 => Host::IpAddr(IpAddr::V4(Ipv4Addr::from(
+                <[u8; 4]>::try_from(addr).unwrap(),
+            ))),
+            ATYP_IPV6 => Host::IpAddr(IpAddr::V6(Ipv6Addr::from(
+                <[u8; 16]>::try_from(addr).unwrap(),
+            ))),
+            _ => {
+                let name =
+                    core::str::from_utf8(&addr[1..]).map_err(|_| Socks5AddrError::InvalidDomain)?;
+                Host::from(name)
+            }
+        };
+
+        let port = u16::from_be_bytes([port[0], port[1]]);
+        Ok((HostPortPair { host, port }, len))
+    }
+
+    /// Reads a SOCKS5 address from the front of `buf`.
+    ///
+    /// On error, `buf` may be partly consumed. To decode from a buffer that may hold a partial
+    /// address, use [`decode_socks5_addr`](Self::decode_socks5_addr) on its bytes.
+    #[cfg(feature = "bytes")]
+    pub fn read_socks5_addr(buf: &mut impl Buf) -> Result<Self, Socks5AddrError> {
+        let mut bytes = [0; MAX_LEN];
+
+        let atyp = *buf.chunk().first().ok_or(Socks5AddrError::Truncated)?;
+        check_addr_type(atyp)?;
+
+        if buf.remaining() < 2 {
+            return Err(Socks5AddrError::Truncated);
+        }
+
+        buf.copy_to_slice(&mut bytes[..2]);
+        let len = addr_len(bytes[0], bytes[1])?;
+
+        if buf.remaining() < len - 2 {
+            return Err(Socks5AddrError::Truncated);
+        }
+
+        buf.copy_to_slice(&mut bytes[2..len]);
+        Self::decode_socks5_addr(&bytes[..len]).map(|(pair, _)| pair)
+    }
+
+    /// Reads a SOCKS5 address from `reader`. A truncated address is reported as
+    /// [`ErrorKind::UnexpectedEof`], and a malformed one as [`ErrorKind::InvalidData`].
+    #[cfg(feature = "std")]
+    pub fn read_socks5_addr_from(reader: &mut impl Read) -> IoResult<Self> {
+        let mut bytes = [0; MAX_LEN];
+        reader.read_exact(&mut bytes[..1])?;
+        check_addr_type(bytes[0])?;
+
+        reader.read_exact(&mut bytes[1..2])?;
+        let len = addr_len(bytes[0], bytes[1])?;
+        reader.read_exact(&mut bytes[2..len])?;
+
+        Ok(Self::decode_socks5_addr(&bytes[..len])?.0)
+    }
+
+    /// Reads a SOCKS5 address from `reader` with `tokio`. A truncated address is reported as
+    /// [`ErrorKind::UnexpectedEof`], and a malformed one as [`ErrorKind::InvalidData`].
+    #[cfg(feature = "tokio")]
+    pub async fn read_socks5_addr_async(reader: &mut (impl AsyncRead + Unpin)) -> IoResult<Self> {
+        let mut bytes = [0; MAX_LEN];
+        reader.read_exact(&mut bytes[..1]).await?;
+        check_addr_type(bytes[0])?;
+
+        reader.read_exact(&mut bytes[1..2]).await?;
+        let len = addr_len(bytes[0], bytes[1])?;
+        reader.read_exact(&mut bytes[2..len]).await?;
+
+        Ok(Self::decode_socks5_addr(&bytes[..len])?.0)
+    }
+
+    fn encode_socks5_addr(&self) -> Result<([u8; MAX_LEN], usize), Socks5AddrError> {
+        let mut bytes = [0; MAX_LEN];
+        let mut len = 0;
+
+        let mut put = |data: &[u8]| {
+            bytes[len..len + data.len()].copy_from_slice(data);
+            len += data.len();
+        };
+
+        match self.host.as_borrowed() {
+            HostRef::IpAddr(IpAddr::V4(ip)) => {
+                put(&[ATYP_IPV4]);
+                put(&ip.octets());
+            }
+            HostRef::IpAddr(IpAddr::V6(ip)) | HostRef::ScopedIpv6(ip, _) => {
+                put(&[ATYP_IPV6]);
+                put(&ip.octets());
+            }
+            HostRef::DnsName("") => return Err(Socks5AddrError::EmptyDomain),
+            HostRef::DnsName(name) => {
+                let name_len =
+                    u8::try_from(name.len()).map_err(|_| Socks5AddrError::DomainTooLong)?;
+                put(&[ATYP_DOMAIN, name_len]);
+                put(name.as_bytes());
+            }
+        }
+
+        put(&self.port.to_be_bytes());
+        Ok((bytes, len))
+    }
+}
+
+#[cfg(feature = "std")]
+impl From<Socks5AddrError> for IoError {
+    fn from(err: Socks5AddrError) -> Self {
+        match err {
+            Socks5AddrError::Truncated => IoError::new(ErrorKind::UnexpectedEof, err),
+            _ => IoError::new(ErrorKind::InvalidData, err),
+        }
+    }
+}
+
+// Checks the address type before the byte after it is needed, so that unknown types fail early.
+fn check_addr_type(atyp: u8) -> Result<(), Socks5AddrError> {
+    match atyp {
+        ATYP_IPV4 | ATYP_DOMAIN | ATYP_IPV6 => Ok(()),
+        atyp => Err(Socks5AddrError::UnknownAddrType(atyp)),
+    }
+}
+
+// The length of an address with type `atyp`, where `second` is the byte after the type, which
+// holds the length of a domain name.
+fn addr_len(atyp: u8, second: u8) -> Result<usize, Socks5AddrError> {
+    match atyp {
+        ATYP_IPV4 => Ok(1 + 4 + 2),
+        ATYP_IPV6 => Ok(1 + 16 + 2),
+        ATYP_DOMAIN if second == 0 => Err(Socks5AddrError::EmptyDomain),
+        ATYP_DOMAIN => Ok(1 + 1 + usize::from(second) + 2),
+        atyp => Err(Socks5AddrError::UnknownAddrType(atyp)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    fn pair(s: &str) -> HostPortPair {
+        HostPortPair::try_from(s).unwrap()
+    }
+
+    #[test]
+    fn encode() {
+        assert_eq!(
+            pair("192.0.2.1:80").to_socks5_addr().unwrap(),
+            [ATYP_IPV4, 192, 0, 2, 1, 0, 80]
+        );
+
+        let mut ipv6 = vec![ATYP_IPV6];
+        ipv6.extend_from_slice(&Ipv6Addr::LOCALHOST.octets());
+        ipv6.extend_from_slice(&443u16.to_be_bytes());
+        assert_eq!(pair("[::1]:443").to_socks5_addr().unwrap(), ipv6);
+
+        // Zones are dropped.
+        let mut scoped = vec![ATYP_IPV6];
+        scoped.extend_from_slice(&"fe80::1".parse::<Ipv6Addr>().unwrap().octets());
+        scoped.extend_from_slice(&22u16.to_be_bytes());
+        assert_eq!(
+            pair("[fe80::1%25eth0]:22").to_socks5_addr().unwrap(),
+            scoped
+        );
+
+        let mut domain = vec![ATYP_DOMAIN, 11];
+        domain.extend_from_slice(b"example.com");
+        domain.extend_from_slice(&8080u16.to_be_bytes());
+        assert_eq!(pair("example.com:8080").to_socks5_addr().unwrap(), domain);
+    }
+
+    #[test]
+    fn encode_errors() {
+        let long = HostPortPair::from((Host::from("a".repeat(256).as_str()), 80));
+        assert_eq!(long.to_socks5_addr(), Err(Socks5AddrError::DomainTooLong));
+
+        let empty = HostPortPair::from((Host::DnsName("".into()), 80));
+        assert_eq!(empty.to_socks5_addr(), Err(Socks5AddrError::EmptyDomain));
+    }
+
+    #[test]
+    fn decode_round_trip() {
+        for s in ["192.0.2.1:80", "[2001:db8::1]:443", "example.com:8080"] {
+            let pair = pair(s);
+            let mut bytes = pair.to_socks5_addr().unwrap();
+            let len = bytes.len();
+            bytes.extend_from_slice(b"rest");
+
+            assert_eq!(
+                HostPortPair::decode_socks5_addr(&bytes).unwrap(),
+                (pair, len)
+            );
+        }
+
+        // IP literals sent as domain names are decoded as addresses.
+        let (pair, _) = HostPortPair::decode_socks5_addr(b"\x03\x09127.0.0.1\x00\x50").unwrap();
+        assert!(pair.host().is_ip_address());
+    }
+
+    #[test]
+    fn decode_errors() {
+        let decode = |bytes: &[u8]| HostPortPair::decode_socks5_addr(bytes).map(|(pair, _)| pair);
+
+        assert_eq!(decode(b""), Err(Socks5AddrError::Truncated));
+        assert_eq!(decode(b"\x01"), Err(Socks5AddrError::Truncated));
+        assert_eq!(
+            decode(b"\x01\x7f\x00\x00\x01\x00"),
+            Err(Socks5AddrError::Truncated)
+        );
+        assert_eq!(decode(b"\x03\x05abc"), Err(Socks5AddrError::Truncated));
+        assert_eq!(decode(b"\x05"), Err(Socks5AddrError::UnknownAddrType(5)));
+        assert_eq!(
+            decode(b"\x03\x00\x00\x50"),
+            Err(Socks5AddrError::EmptyDomain)
+        );
+        assert_eq!(
+            decode(b"\x03\x01\xff\x00\x50"),
+            Err(Socks5AddrError::InvalidDomain)
+        );
+    }
+
+    #[cfg(feature = "bytes")]
+    #[test]
+    fn bytes_round_trip() {
+        let pair = pair("example.com:8080");
+        let mut buf = vec![];
+        pair.write_socks5_addr(&mut buf).unwrap();
+        buf.extend_from_slice(b"rest");
+
+        let mut buf = &buf[..];
+        assert_eq!(HostPortPair::read_socks5_addr(&mut buf).unwrap(), pair);
+        assert_eq!(buf, b"rest");
+
+        let mut truncated = &b"\x03\x0bexample"[..];
+        assert_eq!(
+            HostPortPair::read_socks5_addr(&mut truncated),
+            Err(Socks5AddrError::Truncated)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_round_trip() {
+        let pair = pair("[2001:db8::1]:443");
+        let mut buf = vec![];
+        pair.write_socks5_addr_to(&mut buf).unwrap();
+        buf.extend_from_slice(b"rest");
+
+        let mut reader = &buf[..];
+        assert_eq!(
+            HostPortPair::read_socks5_addr_from(&mut reader).unwrap(),
+            pair
+        );
+        assert_eq!(reader, b"rest");
+
+        let err = HostPortPair::read_socks5_addr_from(&mut &buf[..5]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+
+        let err = HostPortPair::read_socks5_addr_from(&mut &b"\x05"[..]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn tokio_round_trip() {
+        use core::{
+            future::Future,
+            pin::pin,
+            task::{Context, Poll, Waker},
+        };
+
+        // Reading from and writing to memory never waits, so one poll completes the future.
+        fn now<T>(future: impl Future<Output = T>) -> T {
+            match pin!(future).poll(&mut Context::from_waker(Waker::noop())) {
+                Poll::Ready(output) => output,
+                Poll::Pending => panic!("future is pending"),
+            }
+        }
+
+        let pair = pair("example.com:8080");
+        let mut buf = vec![];
+        now(pair.write_socks5_addr_async(&mut buf)).unwrap();
+
+        let mut reader = &buf[..];
+        assert_eq!(
+            now(HostPortPair::read_socks5_addr_async(&mut reader)).unwrap(),
+            pair
+        );
+        assert!(reader.is_empty());
+    }
+}
@@ -0,0 +1,328 @@
+use alloc::vec::Vec;
+use core::{
+    cmp::{Ordering, Reverse},
+    net::{IpAddr, Ipv6Addr, SocketAddr},
+};
+
+/// An entry of an RFC 6724 policy table. IPv4 addresses match as IPv4-mapped IPv6 addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Policy {
+    pub prefix: Ipv6Addr,
+    pub prefix_len: u8,
+    pub precedence: u8,
+    pub label: u8,
+}
+
+/// The policy table that assigns precedences and labels to addresses, by longest prefix match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyTable(Vec<Policy>);
+
+impl PolicyTable {
+    pub fn new(policies: impl IntoIterator<Item = Policy>) -> Self {
+        let mut policies = policies.into_iter().collect::<Vec<_>>();
+        policies.sort_by_key(|policy| Reverse(policy.prefix_len));
+        PolicyTable(policies)
+    }
+
+    pub fn lookup(&self, ip: IpAddr) -> Option<&Policy> {
+        let ip = to_ipv6(ip);
+
+        self.0
+            .iter()
+            .find(|policy| common_prefix_len(ip, policy.prefix) >= policy.prefix_len)
+    }
+}
+
+// The default table of RFC 6724 section 2.1.
+impl Default for PolicyTable {
+    fn default() -> Self {
+        let policy = |prefix: &str, prefix_len, precedence, label| Policy {
+            prefix: prefix.parse().unwrap(),
+            prefix_len,
+            precedence,
+            label,
+        };
+
+        PolicyTable::new([
+            policy("::1", 128, 50, 0),
+            policy("::", 0, 40, 1),
+            policy("::ffff:0:0", 96, 35, 4),
+            policy("2002::", 16, 30, 2),
+            policy("2001::", 32, 5, 5),
+            policy("fc00::", 7, 3, 13),
+            policy("::", 96, 1, 3),
+            policy("fec0::", 10, 1, 11),
+            policy("3ffe::", 16, 1, 12),
+        ])
+    }
+}
+
+/// The source address that would be used to reach a destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Source {
+    pub addr: IpAddr,
+    pub deprecated: bool,
+}
+
+/// Orders destination addresses by the rules of RFC 6724 section 6.
+///
+/// Rules 4 (home addresses) and 7 (native transport) need interface state and are not applied.
+/// Addresses that no rule separates keep their order.
+#[derive(Debug, Clone, Default)]
+pub struct AddrSorter {
+    table: PolicyTable,
+}
+
+impl AddrSorter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_policy_table(mut self, table: PolicyTable) -> Self {
+        self.table = table;
+        self
+    }
+
+    /// Sorts by the rules that only need the destination addresses.
+    pub fn sort(&self, addrs: &mut [SocketAddr]) {
+        self.sort_inner(addrs, |_| None);
+    }
+
+    /// Sorts by all rules, with `source` giving the source address for each destination, or `None`
+    /// if the destination is unreachable.
+    pub fn sort_with_sources(
+        &self,
+        addrs: &mut [SocketAddr],
+        mut source: impl FnMut(&SocketAddr) -> Option<Source>,
+    ) {
+        self.sort_inner(addrs, |addr| Some(source(addr)));
+    }
+
+    fn sort_inner(
+        &self,
+        addrs: &mut [SocketAddr],
+        mut source: impl FnMut(&SocketAddr) -> Option<Option<Source>>,
+    ) {
+        let mut dests = addrs
+            .iter()
+            .map(|addr| Destination::new(*addr, source(addr), &self.table))
+            .collect::<Vec<_>>();
+
+        dests.sort_by(Destination::cmp);
+
+        for (addr, dest) in addrs.iter_mut().zip(dests) {
+            *addr = dest.addr;
+        }
+    }
+}
+
+/// Finds the source address that the system would use for `addr`, without sending any packets.
+#[cfg(feature = "std")]
+pub fn system_source(addr: &SocketAddr) -> Option<Source> {
+    use core::net::Ipv4Addr;
+    use std::net::UdpSocket;
+
+    let local = match addr {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+
+    let socket = UdpSocket::bind(local).ok()?;
+    socket.connect(addr).ok()?;
+
+    Some(Source {
+        addr: socket.local_addr().ok()?.ip(),
+        deprecated: false,
+    })
+}
+
+struct Destination {
+    addr: SocketAddr,
+    scope: u8,
+    precedence: u8,
+    label: u8,
+    // `None` if sources are unknown, `Some(None)` if the destination is unreachable.
+    source: Option<Option<SourceAttrs>>,
+}
+
+struct SourceAttrs {
+    scope: u8,
+    label: u8,
+    deprecated: bool,
+    // The prefix shared with the destination for rule 9, or 0 for IPv4 destinations.
+    prefix_len: u8,
+}
+
+impl Destination {
+    fn new(addr: SocketAddr, source: Option<Option<Source>>, table: &PolicyTable) -> Self {
+        let (precedence, label) = policy(addr.ip(), table);
+
+        let source = source.map(|source| {
+            source.map(|source| SourceAttrs {
+                scope: scope(source.addr),
+                label: policy(source.addr, table).1,
+                deprecated: source.deprecated,
+                prefix_len: match addr {
+                    SocketAddr::V4(_) => 0,
+                    SocketAddr::V6(addr) => common_prefix_len(*addr.ip(), to_ipv6(source.addr)),
+                },
+            })
+        });
+
+        Destination {
+            addr,
+            scope: scope(addr.ip()),
+            precedence,
+            label,
+            source,
+        }
+    }
+
+    // `Less` means that `self` is preferred.
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (Some(a), Some(b)) = (&self.source, &other.source) else {
+            return self.cmp_without_sources(other);
+        };
+
+        let (a, b) = match (a, b) {
+            (Some(a), Some(b)) => (a, b),
+            (Some(_), None) => return Ordering::Less,
+            (None, Some(_)) => return Ordering::Greater,
+            (None, None) => return self.cmp_without_sources(other),
+        };
+
+        // Rule 2: prefer matching scope.
+        let ordering = prefer(self.scope == a.scope, other.scope == b.scope);
+        if ordering.is_ne() {
+            return ordering;
+        }
+
+        // Rule 3: avoid deprecated addresses.
+        let ordering = prefer(!a.deprecated, !b.deprecated);
+        if ordering.is_ne() {
+            return ordering;
+        }
+
+        // Rule 5: prefer matching label.
+        let ordering = prefer(self.label == a.label, other.label == b.label);
+        if ordering.is_ne() {
+            return ordering;
+        }
+
+        let ordering = self.cmp_without_sources(other);
+        if ordering.is_ne() {
+            return ordering;
+        }
+
+        // Rule 9: use longest matching prefix. Like most implementations, this ignores the prefix
+        // of IPv4 addresses, as it would defeat round-robin DNS. Counting it as 0, rather than
+        // only comparing pairs of IPv6 addresses, keeps the order total.
+        b.prefix_len.cmp(&a.prefix_len)
+    }
+
+    // Rules 6 and 8, which only need the destinations.
+    fn cmp_without_sources(&self, other: &Self) -> Ordering {
+        // Rule 6: prefer higher precedence.
+        let ordering = other.precedence.cmp(&self.precedence);
+        if ordering.is_ne() {
+            return ordering;
+        }
+
+        // Rule 8: prefer smaller scope.
+        self.scope.cmp(&other.scope)
+    }
+}
+
+fn prefer(a: bool, b: bool) -> Ordering {
+    b.cmp(&a)
+}
+
+fn policy(ip: IpAddr, table: &PolicyTable) -> (u8, u8) {
+    match table.lookup(ip) {
+        Some(policy) => (policy.precedence, policy.label),
+        None => (0, 0),
+    }
+}
+
+// The scope values of RFC 4291 and RFC 6724 section 3.2.
+fn scope(ip: IpAddr) -> u8 {
+    const LINK_LOCAL: u8 = 0x2;
+    const SITE_LOCAL: u8 = 0x5;
+    const GLOBAL: u8 = 0xe;
+
+    match ip {
+        IpAddr::V4(ip) if ip.is_loopback() || ip.is_link_local() => LINK_LOCAL,
+        IpAddr::V4(_) => GLOBAL,
+        IpAddr::V6(ip) if ip.is_multicast() => ip.octets()[1] & 0x0f,
+        IpAddr::V6(ip) if ip.is_loopback() || ip.is_unicast_link_local() => LINK_LOCAL,
+        IpAddr::V6(ip) if ip.segments()[0] & 0xffc0 == 0xfec0 => SITE_LOCAL,
+        IpAddr::V6(_) => GLOBAL,
+    }
+}
+
+fn to_ipv6(ip: IpAddr) -> Ipv6Addr {
+    match ip {
+        IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+        IpAddr::V6(ip) => ip,
+    }
+}
+
+fn common_prefix_len(a: Ipv6Addr, b: Ipv6Addr) -> u8 {
+    (u128::from(a) ^ u128::from(b)).leading_zeros() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn longest_prefix_is_a_total_order() {
+        // A single policy, so that only rule 9 separates the addresses.
+        let table = PolicyTable::new([Policy {
+            prefix: Ipv6Addr::UNSPECIFIED,
+            prefix_len: 0,
+            precedence: 1,
+            label: 1,
+        }]);
+        let sorter = AddrSorter::new().with_policy_table(table);
+
+        let near = "[2001:db8::1]:80".parse().unwrap();
+        let far = "[3001:db8::1]:80".parse().unwrap();
+        let v4 = "192.0.2.1:80".parse().unwrap();
+
+        let source = |addr: &SocketAddr| {
+            let addr = match addr {
+                SocketAddr::V4(_) => "192.0.2.2".parse().unwrap(),
+                SocketAddr::V6(_) => "2001:db8::2".parse().unwrap(),
+            };
+
+            Some(Source {
+                addr,
+                deprecated: false,
+            })
+        };
+
+        for mut addrs in [
+            [near, far, v4],
+            [near, v4, far],
+            [far, near, v4],
+            [far, v4, near],
+            [v4, near, far],
+            [v4, far, near],
+        ] {
+            sorter.sort_with_sources(&mut addrs, source);
+            assert_eq!(addrs, [near, far, v4]);
+        }
+    }
+
+    #[test]
+    fn default_policy() {
+        let loopback = "[::1]:80".parse().unwrap();
+        let v6 = "[2001:db8::1]:80".parse().unwrap();
+        let v4 = "192.0.2.1:80".parse().unwrap();
+
+        let mut addrs = [v4, v6, loopback];
+        AddrSorter::new().sort(&mut addrs);
+        assert_eq!(addrs, [loopback, v6, v4]);
+    }
+}
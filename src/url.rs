@@ -0,0 +1,233 @@
+use crate::{
+    parse::{is_invalid_host_char, parse_ip_host},
+    split_host_maybe_port, to_ascii_name, Host, HostPortPair, HostRef, ParseError, ParseErrorKind,
+};
+use alloc::{
+    borrow::{Cow, ToOwned},
+    string::String,
+    vec::Vec,
+};
+use core::net::IpAddr;
+
+const DEFAULT_PORTS: &[(&str, u16)] = &[
+    ("amqp", 5672),
+    ("amqps", 5671),
+    ("dns", 53),
+    ("ftp", 21),
+    ("ftps", 990),
+    ("git", 9418),
+    ("gopher", 70),
+    ("http", 80),
+    ("https", 443),
+    ("imap", 143),
+    ("imaps", 993),
+    ("irc", 6667),
+    ("ircs", 6697),
+    ("kafka", 9092),
+    ("ldap", 389),
+    ("ldaps", 636),
+    ("memcached", 11211),
+    ("mongodb", 27017),
+    ("mqtt", 1883),
+    ("mqtts", 8883),
+    ("mysql", 3306),
+    ("nats", 4222),
+    ("nntp", 119),
+    ("pop3", 110),
+    ("pop3s", 995),
+    ("postgres", 5432),
+    ("postgresql", 5432),
+    ("redis", 6379),
+    ("rediss", 6379),
+    ("rtsp", 554),
+    ("sftp", 22),
+    ("sip", 5060),
+    ("sips", 5061),
+    ("smtp", 25),
+    ("smtps", 465),
+    ("socks4", 1080),
+    ("socks4a", 1080),
+    ("socks5", 1080),
+    ("socks5h", 1080),
+    ("ssh", 22),
+    ("telnet", 23),
+    ("ws", 80),
+    ("wss", 443),
+    ("xmpp", 5222),
+];
+
+/// Returns the default port of a well-known URL scheme, compared case-insensitively.
+pub fn default_port(scheme: &str) -> Option<u16> {
+    DEFAULT_PORTS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(scheme))
+        .map(|(_, port)| *port)
+}
+
+impl HostPortPair {
+    /// Extracts the host and port from a URL such as `https://user:pw@example.com/path`.
+    ///
+    /// The userinfo, path, query and fragment are ignored. If the URL has no port, the default
+    /// port of its scheme is used.
+    pub fn from_url(url: &str) -> Result<Self, ParseError> {
+        let Some((scheme, rest)) = url.split_once(':') else {
+            return Err(ParseError::new(ParseErrorKind::InvalidScheme, 0..url.len()));
+        };
+
+        if !is_valid_scheme(scheme) {
+            return Err(ParseError::new(
+                ParseErrorKind::InvalidScheme,
+                0..scheme.len(),
+            ));
+        }
+
+        let Some(rest) = rest.strip_prefix("//") else {
+            let start = scheme.len() + 1;
+            return Err(ParseError::new(
+                ParseErrorKind::NoAuthority,
+                start..url.len(),
+            ));
+        };
+
+        let authority = match rest.find(['/', '?', '#']) {
+            Some(end) => &rest[..end],
+            None => rest,
+        };
+
+        let (host, port) =
+            split_authority(authority).map_err(|err| err.offset(scheme.len() + 3))?;
+
+        let port = match port {
+            Some(port) => port,
+            None => default_port(scheme).ok_or_else(|| {
+                ParseError::new(
+                    ParseErrorKind::UnknownScheme(scheme.to_owned()),
+                    0..scheme.len(),
+                )
+            })?,
+        };
+
+        Ok(HostPortPair { host, port })
+    }
+
+    /// Parses a URL authority such as `user:pw@example.com:8080`, ignoring the userinfo.
+    pub fn from_authority(authority: &str) -> Result<Self, ParseError> {
+        match split_authority(authority)? {
+            (host, Some(port)) => Ok(HostPortPair { host, port }),
+            (_, None) => Err(ParseError::new(
+                ParseErrorKind::NoPort,
+                authority.len()..authority.len(),
+            )),
+        }
+    }
+}
+
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+pub(crate) fn split_authority(authority: &str) -> Result<(Host, Option<u16>), ParseError> {
+    let start = match authority.rfind('@') {
+        Some(at) => at + 1,
+        None => 0,
+    };
+
+    // RFC 3986 allows an empty port, which means the same as omitting it.
+    let host_port = &authority[start..];
+    let host_port = host_port.strip_suffix(':').unwrap_or(host_port);
+
+    let (host, port) = split_host_maybe_port(host_port).map_err(|err| err.offset(start))?;
+
+    let host = match host {
+        HostRef::DnsName(name) => {
+            let decoded = percent_decode(name).map_err(|err| err.offset(start))?;
+            let span = start..start + name.len();
+
+            // Decoding can reveal an address, such as `127%2E0.0.1`, or characters that would
+            // change the meaning of the host once it is formatted again.
+            match parse_ip_host(&decoded) {
+                Some(HostRef::IpAddr(ip @ IpAddr::V4(_))) => Host::IpAddr(ip),
+                Some(_) => return Err(ParseError::new(ParseErrorKind::UnbracketedIpv6, span)),
+                None => match decoded
+                    .chars()
+                    .find(|&c| is_invalid_host_char(c) || matches!(c, ':' | '%'))
+                {
+                    Some(c) => {
+                        return Err(ParseError::new(ParseErrorKind::InvalidHostChar(c), span))
+                    }
+                    None => Host::DnsName(to_ascii_name(decoded.into_owned())),
+                },
+            }
+        }
+        HostRef::IpAddr(IpAddr::V6(_)) | HostRef::ScopedIpv6(..) if !host_port.starts_with('[') => {
+            return Err(ParseError::new(
+                ParseErrorKind::UnbracketedIpv6,
+                start..start + host_port.len(),
+            ));
+        }
+        host => host.to_owned(),
+    };
+
+    Ok((host, port))
+}
+
+pub(crate) fn percent_decode(s: &str) -> Result<Cow<'_, str>, ParseError> {
+    if !s.contains('%') {
+        return Ok(Cow::Borrowed(s));
+    }
+
+    let error = |span| ParseError::new(ParseErrorKind::InvalidPercentEncoding, span);
+    let hex = |b: Option<&u8>| b.and_then(|b| char::from(*b).to_digit(16));
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(s.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'%' {
+            decoded.push(bytes[i]);
+            i += 1;
+            continue;
+        }
+
+        match (hex(bytes.get(i + 1)), hex(bytes.get(i + 2))) {
+            (Some(high), Some(low)) => decoded.push((high << 4 | low) as u8),
+            _ => return Err(error(i..(i + 3).min(s.len()))),
+        }
+
+        i += 3;
+    }
+
+    String::from_utf8(decoded)
+        .map(Cow::Owned)
+        .map_err(|_| error(0..s.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+    use core::net::Ipv4Addr;
+
+    #[test]
+    fn percent_encoded_hosts() {
+        let pair = HostPortPair::from_url("http://127%2E0.0.1/").unwrap();
+        assert_eq!(pair.host(), &Host::IpAddr(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert!(pair.host().is_loopback());
+
+        let pair = HostPortPair::from_url("http://ex%61mple.com:8080/").unwrap();
+        assert_eq!(pair.to_string(), "example.com:8080");
+
+        let err = HostPortPair::from_url("http://a%2Fb/").unwrap_err();
+        assert_eq!(err.kind(), &ParseErrorKind::InvalidHostChar('/'));
+        assert_eq!(err.span(), 7..12);
+
+        let err = HostPortPair::from_url("http://a%3A1/").unwrap_err();
+        assert_eq!(err.kind(), &ParseErrorKind::InvalidHostChar(':'));
+
+        let err = HostPortPair::from_url("http://%3A%3A1/").unwrap_err();
+        assert_eq!(err.kind(), &ParseErrorKind::UnbracketedIpv6);
+    }
+}